@@ -0,0 +1,250 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use eframe::egui::{
+    RichText,
+    Sense,
+    Ui,
+    Vec2,
+    CursorIcon,
+};
+
+use super::panel::Panel;
+
+/// Where the kernel lists batteries, as BAT0, BAT1, ...
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+/// How often the battery is read unless --refresh-ms says otherwise
+pub const DEFAULT_REFRESH: Duration = Duration::from_secs(5);
+/// Height of the collapsed widget, including the frame padding
+const BATTERY_HEIGHT: f32 = 48.0;
+/// Extra height of the time estimate shown when expanded
+const DETAILS_HEIGHT: f32 = 24.0;
+/// Height of the expanded widget
+const BATTERY_MAX_HEIGHT: f32 = BATTERY_HEIGHT + DETAILS_HEIGHT;
+const BATTERY_MARGIN: f32 = 8.0;
+/// Width of the widget
+const BATTERY_WIDTH: f32 = 220.0;
+/// Charge below which the battery is drawn in the warning color
+const LOW_CAPACITY: u8 = 20;
+
+/// Options for the battery widget
+#[derive(Debug, Clone)]
+pub struct BatteryOptions {
+    /// How often the battery is read
+    pub refresh_interval: Duration,
+}
+
+/// Charging state as reported in the battery's `status` file
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BatteryStatus {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+    Unknown,
+}
+
+impl BatteryStatus {
+    fn from_sysfs(value: &str) -> Self {
+        match value.trim() {
+            "Charging" => BatteryStatus::Charging,
+            "Discharging" => BatteryStatus::Discharging,
+            "Full" => BatteryStatus::Full,
+            "Not charging" => BatteryStatus::NotCharging,
+            _ => BatteryStatus::Unknown,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            BatteryStatus::Charging => "Charging",
+            BatteryStatus::Discharging => "Discharging",
+            BatteryStatus::Full => "Fully charged",
+            BatteryStatus::NotCharging => "Not charging",
+            BatteryStatus::Unknown => "Unknown",
+        }
+    }
+}
+
+/// One reading of the first battery
+#[derive(Debug, Clone)]
+struct Battery {
+    /// Charge in percent
+    capacity: u8,
+    status: BatteryStatus,
+    /// Time until empty while discharging, or until full while charging
+    time_left: Option<Duration>,
+}
+
+impl Battery {
+    /// Reads the first battery the kernel knows about
+    fn read() -> Option<Self> {
+        let mut batteries: Vec<PathBuf> = fs::read_dir(POWER_SUPPLY_PATH)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("BAT")))
+            .collect();
+        batteries.sort();
+        let path = batteries.first()?;
+
+        let capacity = read_value(path, "capacity")?.min(100) as u8;
+        let status = fs::read_to_string(path.join("status"))
+            .map_or(BatteryStatus::Unknown, |status| BatteryStatus::from_sysfs(&status));
+        Some(Self { capacity, status, time_left: Self::time_left(path, status) })
+    }
+
+    /// Estimate from the current draw; batteries report either energy (µWh, µW)
+    /// or charge (µAh, µA), and the units cancel out either way
+    fn time_left(path: &Path, status: BatteryStatus) -> Option<Duration> {
+        let (now, full, rate) = match read_value(path, "energy_now") {
+            Some(now) => (now, read_value(path, "energy_full")?, read_value(path, "power_now")?),
+            None => (read_value(path, "charge_now")?, read_value(path, "charge_full")?, read_value(path, "current_now")?),
+        };
+        if rate == 0 {
+            return None;
+        }
+        let remaining = match status {
+            BatteryStatus::Discharging => now,
+            BatteryStatus::Charging => full.saturating_sub(now),
+            _ => return None,
+        };
+        Some(Duration::from_secs_f64(remaining as f64 / rate as f64 * 3600.0))
+    }
+}
+
+/// Reads a numeric sysfs attribute of the battery at `path`
+fn read_value(path: &Path, name: &str) -> Option<u64> {
+    fs::read_to_string(path.join(name)).ok()?.trim().parse().ok()
+}
+
+/// Formats a duration like `2 h 05 min`
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    if minutes < 60 {
+        format!("{} min", minutes)
+    } else {
+        format!("{} h {:02} min", minutes / 60, minutes % 60)
+    }
+}
+
+/// Battery charge and time estimate
+pub struct BatteryWidget {
+    colors: super::Colors,
+    options: BatteryOptions,
+    battery: Option<Battery>,
+    expanded: bool,
+    last_update: Instant,
+    size: Vec2,
+}
+
+impl BatteryWidget {
+    pub fn new(colors: super::Colors, options: BatteryOptions) -> Self {
+        let mut widget = Self {
+            colors,
+            options,
+            battery: None,
+            expanded: false,
+            last_update: Instant::now(),
+            size: Vec2::new(BATTERY_WIDTH, BATTERY_HEIGHT),
+        };
+        widget.update();
+        widget
+    }
+
+    fn get_battery_icon(battery: &Battery) -> &'static str {
+        if battery.status == BatteryStatus::Charging {
+            return egui_phosphor::regular::BATTERY_CHARGING;
+        }
+        match battery.capacity {
+            90.. => egui_phosphor::regular::BATTERY_FULL,
+            60.. => egui_phosphor::regular::BATTERY_HIGH,
+            35.. => egui_phosphor::regular::BATTERY_MEDIUM,
+            LOW_CAPACITY.. => egui_phosphor::regular::BATTERY_LOW,
+            10.. => egui_phosphor::regular::BATTERY_WARNING,
+            _ => egui_phosphor::regular::BATTERY_EMPTY,
+        }
+    }
+
+    /// Line shown under the charge when the widget is expanded
+    fn details(battery: &Battery) -> String {
+        match (battery.status, battery.time_left) {
+            (BatteryStatus::Discharging, Some(time)) => format!("{} until empty", format_duration(time)),
+            (BatteryStatus::Charging, Some(time)) => format!("{} until full", format_duration(time)),
+            (status, _) => status.label().to_string(),
+        }
+    }
+}
+
+impl Panel for BatteryWidget {
+    fn next_update(&self) -> Option<Duration> {
+        Some(self.options.refresh_interval.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        self.last_update.elapsed() > self.options.refresh_interval
+    }
+
+    fn update(&mut self) {
+        self.battery = Battery::read();
+        self.last_update = Instant::now();
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    fn max_size(&self) -> Vec2 {
+        Vec2::new(BATTERY_WIDTH, BATTERY_MAX_HEIGHT)
+    }
+
+    fn margin(&self) -> f32 {
+        BATTERY_MARGIN
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        let response = ui.vertical(|ui| {
+            ui.set_width(BATTERY_WIDTH - 2.0 * BATTERY_MARGIN);
+
+            let Some(battery) = &self.battery else {
+                ui.horizontal(|ui| {
+                    ui.set_height(BATTERY_HEIGHT - 2.0 * BATTERY_MARGIN);
+                    ui.label(RichText::new(egui_phosphor::regular::BATTERY_EMPTY).color(self.colors.outline).size(22.0));
+                    ui.label(RichText::new("No battery").color(self.colors.on_surface_variant).size(16.0));
+                });
+                return;
+            };
+
+            // Plugged in is never alarming, however low the charge
+            let low = battery.capacity < LOW_CAPACITY && battery.status != BatteryStatus::Charging;
+            let color = if low { super::WARNING_COLOR } else { self.colors.primary_fixed_dim };
+            ui.horizontal(|ui| {
+                ui.set_height(BATTERY_HEIGHT - 2.0 * BATTERY_MARGIN);
+                ui.label(RichText::new(Self::get_battery_icon(battery)).color(color).size(22.0));
+                ui.label(RichText::new(format!("{}%", battery.capacity)).color(color).size(16.0));
+            });
+            if self.expanded {
+                ui.horizontal(|ui| {
+                    ui.set_height(DETAILS_HEIGHT);
+                    ui.label(RichText::new(Self::details(battery)).color(self.colors.on_surface_variant).size(14.0));
+                });
+            }
+        })
+        .response;
+
+        // Click anywhere on the panel to show or hide the time estimate
+        let response = response.interact(Sense::click()).on_hover_cursor(CursorIcon::PointingHand);
+        if response.clicked() && self.battery.is_some() {
+            self.expanded = !self.expanded;
+        }
+
+        let height = if self.expanded { BATTERY_MAX_HEIGHT } else { BATTERY_HEIGHT };
+        self.size = Vec2::new(BATTERY_WIDTH, height);
+    }
+}
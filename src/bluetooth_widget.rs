@@ -0,0 +1,502 @@
+use std::{
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui::{
+    Align,
+    Button,
+    Color32,
+    Context,
+    Layout,
+    RichText,
+    ScrollArea,
+    Sense,
+    Shape,
+    Spinner,
+    Stroke,
+    Ui,
+    Vec2,
+};
+
+use super::panel::Panel;
+
+/// How often devices are listed unless --refresh-ms says otherwise
+pub const DEFAULT_REFRESH: Duration = Duration::from_millis(2000);
+const BLUETOOTH_MARGIN: f32 = 8.0;
+const BLUETOOTH_WIDTH: f32 = 360.0;
+const BLUETOOTH_HEIGHT: f32 = 320.0;
+/// Height of the summary row above the device list
+const HEADER_HEIGHT: f32 = 28.0;
+/// Height of a device row, without its buttons
+const ROW_HEIGHT: f32 = 32.0;
+
+/// Options for the Bluetooth widget
+#[derive(Debug, Clone)]
+pub struct BluetoothOptions {
+    /// How often devices are listed
+    pub refresh_interval: Duration,
+    /// Corner radius of the panel; buttons are capped at their default radius
+    pub rounding: Option<f32>,
+}
+
+/// Paired or discovered device
+#[derive(Debug, Clone)]
+struct Device {
+    mac: String,
+    name: String,
+    paired: bool,
+    connected: bool,
+    /// freedesktop icon name reported by BlueZ, e.g. audio-headset
+    icon: String,
+}
+
+impl Device {
+    fn get_icon(&self) -> &'static str {
+        match self.icon.as_str() {
+            "audio-headset" => egui_phosphor::regular::HEADSET,
+            "audio-headphones" => egui_phosphor::regular::HEADPHONES,
+            "audio-card" => egui_phosphor::regular::SPEAKER_HIFI,
+            "input-keyboard" => egui_phosphor::regular::KEYBOARD,
+            "input-mouse" | "input-tablet" => egui_phosphor::regular::MOUSE,
+            "input-gaming" => egui_phosphor::regular::GAME_CONTROLLER,
+            "phone" => egui_phosphor::regular::DEVICE_MOBILE,
+            "computer" => egui_phosphor::regular::LAPTOP,
+            _ => egui_phosphor::regular::BLUETOOTH,
+        }
+    }
+}
+
+/// MAC addresses and names from `bluetoothctl devices`, one `Device <mac> <name>` per line
+fn parse_devices(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("Device "))
+        .filter_map(|rest| {
+            let (mac, name) = rest.split_once(' ').unwrap_or((rest, rest));
+            Some((mac.to_string(), name.trim().to_string())).filter(|(mac, _)| !mac.is_empty())
+        })
+        .collect()
+}
+
+/// Value of `key` in `bluetoothctl info` output, whose lines read `\tKey: value`
+fn info_value<'a>(info: &'a str, key: &str) -> Option<&'a str> {
+    info.lines().find_map(|line| {
+        let (name, value) = line.trim().split_once(':')?;
+        (name == key).then(|| value.trim())
+    })
+}
+
+/// Lists the known devices with their state; blocks while bluetoothctl runs
+fn list_devices() -> Vec<Device> {
+    let Some(output) = Command::new("bluetoothctl")
+        .arg("devices")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut devices: Vec<Device> = parse_devices(&output)
+        .into_iter()
+        .map(|(mac, name)| {
+            let info = Command::new("bluetoothctl")
+                .args(["info", &mac])
+                .output()
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+                .unwrap_or_default();
+            Device {
+                paired: info_value(&info, "Paired") == Some("yes"),
+                connected: info_value(&info, "Connected") == Some("yes"),
+                icon: info_value(&info, "Icon").unwrap_or_default().to_string(),
+                mac,
+                name,
+            }
+        })
+        .collect();
+
+    // Connected first, then paired, then whatever the scan turned up
+    devices.sort_by(|a, b| {
+        b.connected.cmp(&a.connected)
+            .then(b.paired.cmp(&a.paired))
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    devices
+}
+
+/// Outcome of a bluetoothctl action run for a device
+struct ActionResult {
+    mac: String,
+    /// bluetoothctl's message when the action failed
+    error: Option<String>,
+}
+
+/// Paired and discovered Bluetooth devices
+pub struct BluetoothWidget {
+    colors: super::Colors,
+    options: BluetoothOptions,
+    devices: Vec<Device>,
+    expanded_device: Option<String>,
+    /// Running `bluetoothctl scan on`; discovery lasts as long as it does
+    scan: Option<Child>,
+    refresh_sender: Sender<Vec<Device>>,
+    refresh_results: Receiver<Vec<Device>>,
+    /// Set by the refresh thread once its result is waiting in the channel
+    refresh_ready: Arc<AtomicBool>,
+    /// Context the refresh thread wakes; known once the widget has been shown
+    repaint_ctx: Arc<OnceLock<Context>>,
+    refreshing: bool,
+    /// False until the first refresh has come back
+    loaded: bool,
+    action_sender: Sender<ActionResult>,
+    action_results: Receiver<ActionResult>,
+    /// Device MACs with an action in flight
+    pending: Vec<String>,
+    /// Last failed action, shown under its device until something succeeds
+    action_error: Option<(String, String)>,
+    last_update: Instant,
+}
+
+impl BluetoothWidget {
+    pub fn new(colors: super::Colors, options: BluetoothOptions) -> Self {
+        let (refresh_sender, refresh_results) = mpsc::channel();
+        let (action_sender, action_results) = mpsc::channel();
+        let mut widget = Self {
+            colors,
+            options,
+            devices: Vec::new(),
+            expanded_device: None,
+            scan: None,
+            refresh_sender,
+            refresh_results,
+            refresh_ready: Arc::new(AtomicBool::new(false)),
+            repaint_ctx: Arc::new(OnceLock::new()),
+            refreshing: false,
+            loaded: false,
+            action_sender,
+            action_results,
+            pending: Vec::new(),
+            action_error: None,
+            last_update: Instant::now(),
+        };
+        widget.start_refresh();
+        widget
+    }
+
+    /// Lists devices on a background thread; the result is picked up by `update`
+    fn start_refresh(&mut self) {
+        self.refreshing = true;
+        self.last_update = Instant::now();
+
+        let sender = self.refresh_sender.clone();
+        let ready = self.refresh_ready.clone();
+        let repaint_ctx = self.repaint_ctx.clone();
+        thread::spawn(move || {
+            if sender.send(list_devices()).is_ok() {
+                ready.store(true, Ordering::Release);
+                if let Some(ctx) = repaint_ctx.get() {
+                    ctx.request_repaint();
+                }
+            }
+        });
+    }
+
+    /// Runs `bluetoothctl <action> <mac>` in the background; `update` picks up the outcome
+    fn run_action(&mut self, action: &'static str, mac: &str) {
+        if self.action_error.as_ref().is_some_and(|(failed, _)| failed == mac) {
+            self.action_error = None;
+        }
+        self.pending.push(mac.to_string());
+
+        let mac = mac.to_string();
+        let sender = self.action_sender.clone();
+        let repaint_ctx = self.repaint_ctx.clone();
+        thread::spawn(move || {
+            let error = match Command::new("bluetoothctl").args([action, &mac]).output() {
+                // bluetoothctl exits successfully even when an action fails,
+                // so look for its failure message too
+                Ok(output) => {
+                    let stdout = String::from_utf8_lossy(&output.stdout);
+                    stdout.lines()
+                        .map(str::trim)
+                        .find(|line| line.starts_with("Failed") || line.contains("not available"))
+                        .map(str::to_string)
+                        .or_else(|| (!output.status.success()).then(|| format!("bluetoothctl failed ({})", output.status)))
+                }
+                Err(err) => Some(format!("Failed to run bluetoothctl: {}", err)),
+            };
+            sender.send(ActionResult { mac, error }).ok();
+            if let Some(ctx) = repaint_ctx.get() {
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// Starts or stops discovering new devices
+    fn toggle_scan(&mut self) {
+        match self.scan.take() {
+            // Waited for off the UI thread, so neither process is left behind as a zombie
+            Some(mut scan) => {
+                thread::spawn(move || {
+                    scan.kill().ok();
+                    scan.wait().ok();
+                    Command::new("bluetoothctl").args(["scan", "off"]).stdout(Stdio::null()).status().ok();
+                });
+            }
+            None => {
+                self.scan = Command::new("bluetoothctl")
+                    .args(["scan", "on"])
+                    .stdout(Stdio::null())
+                    .spawn()
+                    .map_err(|err| eprintln!("Warning: could not start a Bluetooth scan: {}", err))
+                    .ok();
+            }
+        }
+    }
+
+    /// Buttons follow --rounding down to sharp corners, but don't grow past their default
+    fn control_radius(&self) -> u8 {
+        super::corner_radius(self.options.rounding, 6).min(6)
+    }
+
+    fn show_header(&mut self, ui: &mut Ui) {
+        let connected = self.devices.iter().filter(|device| device.connected).count();
+        let (color, label) = if connected > 0 {
+            (self.colors.primary_fixed_dim, format!("{} connected", connected))
+        } else {
+            (self.colors.outline, "Not connected".to_string())
+        };
+
+        ui.horizontal(|ui| {
+            ui.set_height(HEADER_HEIGHT);
+            ui.add_space(8.0);
+            ui.label(RichText::new(egui_phosphor::regular::BLUETOOTH).color(color).size(20.0));
+            ui.add_space(4.0);
+            ui.label(RichText::new(label).color(color).size(14.0));
+
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                ui.add_space(8.0);
+                let scanning = self.scan.is_some();
+                let scan_color = if scanning { self.colors.primary_fixed_dim } else { self.colors.outline };
+                if ui.add(
+                    Button::new(RichText::new(egui_phosphor::regular::MAGNIFYING_GLASS).color(scan_color).size(18.0))
+                    .fill(Color32::TRANSPARENT)
+                    .frame(false)
+                ).on_hover_text(if scanning { "Stop scanning" } else { "Scan for devices" }).clicked() {
+                    self.toggle_scan();
+                }
+                if scanning {
+                    ui.add(Spinner::new().size(14.0).color(self.colors.outline));
+                }
+            });
+        });
+    }
+
+    fn show_device(&mut self, ui: &mut Ui, device: &Device) {
+        let is_expanded = self.expanded_device.as_ref() == Some(&device.mac);
+        let color = if device.connected { self.colors.primary_fixed_dim } else { self.colors.on_surface_variant };
+        // Reserved before the row so the hover fill ends up underneath its content
+        let hover_fill = ui.painter().add(Shape::Noop);
+
+        let response = ui.horizontal(|ui| {
+            ui.set_height(ROW_HEIGHT);
+            ui.add_space(8.0);
+            ui.label(RichText::new(device.get_icon()).color(color).size(18.0));
+            ui.add_space(4.0);
+            ui.label(RichText::new(&device.name).color(color).size(16.0));
+
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                ui.add_space(8.0);
+                if self.pending.contains(&device.mac) {
+                    ui.add(Spinner::new().size(16.0).color(self.colors.primary_fixed_dim));
+                } else if device.connected {
+                    ui.label(RichText::new(egui_phosphor::regular::BLUETOOTH_CONNECTED).color(color).size(18.0));
+                } else if device.paired {
+                    ui.label(RichText::new("Paired").color(self.colors.outline).size(13.0));
+                }
+            });
+        }).response;
+
+        let response = response.interact(Sense::click());
+        if response.clicked() {
+            self.expanded_device = if is_expanded { None } else { Some(device.mac.clone()) };
+        }
+        if response.hovered() {
+            ui.painter().set(
+                hover_fill,
+                Shape::rect_filled(response.rect, self.control_radius(), self.colors.surface_container.gamma_multiply(0.5)),
+            );
+        }
+
+        if is_expanded {
+            ui.horizontal(|ui| {
+                ui.set_height(ROW_HEIGHT + 4.0);
+                ui.add_space(8.0);
+                ui.label(RichText::new(&device.mac).monospace().color(self.colors.outline).size(12.0));
+
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    ui.add_space(8.0);
+                    let (action, icon, label) = if device.connected {
+                        ("disconnect", egui_phosphor::regular::LINK_BREAK, "Disconnect")
+                    } else if device.paired {
+                        ("connect", egui_phosphor::regular::LINK, "Connect")
+                    } else {
+                        ("pair", egui_phosphor::regular::PLUS, "Pair")
+                    };
+                    let busy = self.pending.contains(&device.mac);
+                    if ui.add_enabled(
+                        !busy,
+                        Button::new(RichText::new(format!("{} {}", icon, label)).color(self.colors.primary_fixed_dim).size(14.0))
+                        .fill(self.colors.surface_container)
+                        .corner_radius(self.control_radius())
+                        .stroke(Stroke::new(1.5, self.colors.primary_fixed_dim))
+                        .min_size(Vec2::new(104.0, 28.0))
+                    ).clicked() {
+                        self.run_action(action, &device.mac);
+                    }
+                });
+            });
+        }
+
+        if let Some((_, message)) = self.action_error.as_ref().filter(|(failed, _)| *failed == device.mac) {
+            ui.horizontal(|ui| {
+                ui.add_space(8.0);
+                ui.add(eframe::egui::Label::new(RichText::new(message).color(super::WARNING_COLOR).size(13.0)).wrap());
+            });
+        }
+    }
+}
+
+impl Panel for BluetoothWidget {
+    fn next_update(&self) -> Option<Duration> {
+        Some(self.options.refresh_interval.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        self.last_update.elapsed() > self.options.refresh_interval || self.refresh_ready.load(Ordering::Acquire)
+    }
+
+    /// Applies finished refreshes and actions, and starts the next refresh when it's due
+    fn update(&mut self) {
+        let mut action_finished = false;
+        while let Ok(result) = self.action_results.try_recv() {
+            self.pending.retain(|mac| *mac != result.mac);
+            match result.error {
+                Some(message) => {
+                    eprintln!("Warning: bluetoothctl failed for {}: {}", result.mac, message);
+                    self.action_error = Some((result.mac, message));
+                }
+                None => {
+                    if self.action_error.as_ref().is_some_and(|(failed, _)| *failed == result.mac) {
+                        self.action_error = None;
+                    }
+                }
+            }
+            action_finished = true;
+        }
+
+        self.refresh_ready.store(false, Ordering::Release);
+        while let Ok(devices) = self.refresh_results.try_recv() {
+            self.devices = devices;
+            self.refreshing = false;
+            self.loaded = true;
+        }
+
+        let due = action_finished || self.last_update.elapsed() > self.options.refresh_interval;
+        if !self.refreshing && due {
+            self.start_refresh();
+        }
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(BLUETOOTH_WIDTH, BLUETOOTH_HEIGHT)
+    }
+
+    fn margin(&self) -> f32 {
+        BLUETOOTH_MARGIN
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        // Lets the background threads wake the UI when their results are in
+        self.repaint_ctx.get_or_init(|| ui.ctx().clone());
+
+        ui.vertical(|ui| {
+            ui.set_width(BLUETOOTH_WIDTH - 2.0 * BLUETOOTH_MARGIN);
+            ui.set_height(BLUETOOTH_HEIGHT - 2.0 * BLUETOOTH_MARGIN);
+
+            self.show_header(ui);
+
+            ScrollArea::vertical()
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    if !self.loaded {
+                        ui.add_space(48.0);
+                        ui.vertical_centered(|ui| {
+                            ui.add(Spinner::new().size(32.0).color(self.colors.outline));
+                        });
+                        return;
+                    }
+                    if self.devices.is_empty() {
+                        ui.add_space(48.0);
+                        ui.vertical_centered(|ui| {
+                            ui.label(RichText::new(egui_phosphor::regular::BLUETOOTH_SLASH).color(self.colors.outline).size(32.0));
+                            ui.add_space(4.0);
+                            ui.label(RichText::new("No devices found").color(self.colors.on_surface_variant).size(16.0));
+                        });
+                        return;
+                    }
+                    for device in self.devices.clone() {
+                        self.show_device(ui, &device);
+                    }
+                });
+        });
+    }
+}
+
+impl Drop for BluetoothWidget {
+    fn drop(&mut self) {
+        // Don't leave discovery running once the widget is gone
+        if self.scan.is_some() {
+            self.toggle_scan();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_device_list() {
+        let output = "Device 00:1A:7D:DA:71:13 WH-1000XM4\nDevice 5C:F3:70:8B:12:01 Magic Keyboard  \nController 00:00:00:00:00:00 laptop\nDevice AA:BB:CC:DD:EE:FF\n";
+        assert_eq!(parse_devices(output), [
+            ("00:1A:7D:DA:71:13".to_string(), "WH-1000XM4".to_string()),
+            ("5C:F3:70:8B:12:01".to_string(), "Magic Keyboard".to_string()),
+            // Unnamed devices go by their address
+            ("AA:BB:CC:DD:EE:FF".to_string(), "AA:BB:CC:DD:EE:FF".to_string()),
+        ]);
+        assert!(parse_devices("No default controller available\n").is_empty());
+    }
+
+    #[test]
+    fn reads_info_values() {
+        let info = "Device 00:1A:7D:DA:71:13 (public)\n\tName: WH-1000XM4\n\tIcon: audio-headset\n\tPaired: yes\n\tConnected: no\n\tUUID: Audio Sink (0000110b-0000-1000-8000-00805f9b34fb)\n";
+        assert_eq!(info_value(info, "Icon"), Some("audio-headset"));
+        assert_eq!(info_value(info, "Paired"), Some("yes"));
+        assert_eq!(info_value(info, "Connected"), Some("no"));
+        assert_eq!(info_value(info, "UUID"), Some("Audio Sink (0000110b-0000-1000-8000-00805f9b34fb)"));
+        assert_eq!(info_value(info, "Trusted"), None);
+    }
+}
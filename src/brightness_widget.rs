@@ -0,0 +1,281 @@
+use std::{
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui::{
+    Context,
+    Key,
+    RichText,
+    Slider,
+    Ui,
+    Vec2,
+};
+
+use super::panel::Panel;
+use super::shortcut::{self, Shortcut};
+
+/// How often the brightness is read unless --refresh-ms says otherwise
+pub const DEFAULT_REFRESH: Duration = Duration::from_millis(1000);
+const BRIGHTNESS_MARGIN: f32 = 8.0;
+const BRIGHTNESS_WIDTH: f32 = 320.0;
+const BRIGHTNESS_HEIGHT: f32 = 48.0;
+/// Percent changed by an arrow key or a scroll wheel notch
+const STEP: i32 = 5;
+/// Lowest settable brightness; 0% turns many panels off entirely
+const MIN_PERCENT: i32 = 1;
+/// Scroll events within this long after a step are ignored, so one notch moves one step
+const SCROLL_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Options for the brightness widget
+#[derive(Debug, Clone)]
+pub struct BrightnessOptions {
+    /// How often the brightness is read
+    pub refresh_interval: Duration,
+    /// Whether ↑ / ↓ change the brightness anywhere in the window; when another
+    /// widget also uses them, they only do while the pointer is over this one
+    pub arrow_keys: bool,
+}
+
+/// Runs brightnessctl and parses its output as a number
+fn brightnessctl_value(command: &str) -> Option<u32> {
+    let output = Command::new("brightnessctl").arg(command).output().ok()?;
+    String::from_utf8(output.stdout).ok()?.trim().parse().ok()
+}
+
+/// Current brightness in percent, or None without brightnessctl or a backlight
+fn read_percent() -> Option<i32> {
+    match (brightnessctl_value("get"), brightnessctl_value("max")) {
+        (Some(current), Some(max)) if max > 0 => Some((current as f32 * 100.0 / max as f32).round() as i32),
+        _ => None,
+    }
+}
+
+/// Work for the brightnessctl thread
+enum BrightnessRequest {
+    Read,
+    Set(i32),
+}
+
+/// Brightness read back by the worker, and the percentage it set just before, if any
+struct Reading {
+    percent: Option<i32>,
+    after_set: Option<i32>,
+}
+
+/// Runs brightnessctl off the UI thread, one batch of requests at a time
+///
+/// Only the latest percentage of a batch is set, so the last slider position wins,
+/// and every batch ends with a reading that `update` picks up.
+fn spawn_worker(readings: Sender<Reading>, ready: Arc<AtomicBool>, repaint_ctx: Arc<OnceLock<Context>>) -> Sender<BrightnessRequest> {
+    let (sender, receiver) = mpsc::channel::<BrightnessRequest>();
+    thread::spawn(move || {
+        while let Ok(request) = receiver.recv() {
+            let after_set = std::iter::once(request)
+                .chain(receiver.try_iter())
+                .filter_map(|request| match request {
+                    BrightnessRequest::Set(percent) => Some(percent),
+                    BrightnessRequest::Read => None,
+                })
+                .last();
+            if let Some(percent) = after_set {
+                Command::new("brightnessctl")
+                    .args(["set", &format!("{}%", percent)])
+                    .stdout(Stdio::null())
+                    .status()
+                    .ok();
+            }
+
+            if readings.send(Reading { percent: read_percent(), after_set }).is_err() {
+                break;
+            }
+            ready.store(true, Ordering::Release);
+            if let Some(ctx) = repaint_ctx.get() {
+                ctx.request_repaint();
+            }
+        }
+    });
+    sender
+}
+
+/// Backlight slider of the default display
+pub struct BrightnessWidget {
+    colors: super::Colors,
+    options: BrightnessOptions,
+    /// Brightness in percent; None without brightnessctl or a backlight
+    percent: Option<i32>,
+    /// False until the first reading has come back
+    loaded: bool,
+    /// Polling waits while the slider is held so it doesn't jump back
+    dragging: bool,
+    /// When scrolling last stepped the brightness
+    last_scroll_step: Option<Instant>,
+    /// Worker running brightnessctl; it exits once the widget is dropped
+    requests: Sender<BrightnessRequest>,
+    readings: Receiver<Reading>,
+    /// Set by the worker once a reading is waiting in the channel
+    refresh_ready: Arc<AtomicBool>,
+    /// Context the worker wakes; known once the widget has been shown
+    repaint_ctx: Arc<OnceLock<Context>>,
+    /// Last percentage sent to the worker; readings taken before it was set are stale
+    requested: Option<i32>,
+    last_update: Instant,
+}
+
+impl BrightnessWidget {
+    pub fn new(colors: super::Colors, options: BrightnessOptions) -> Self {
+        let (reading_sender, readings) = mpsc::channel();
+        let refresh_ready = Arc::new(AtomicBool::new(false));
+        let repaint_ctx = Arc::new(OnceLock::new());
+        let requests = spawn_worker(reading_sender, refresh_ready.clone(), repaint_ctx.clone());
+        requests.send(BrightnessRequest::Read).ok();
+        Self {
+            colors,
+            options,
+            percent: None,
+            loaded: false,
+            dragging: false,
+            last_scroll_step: None,
+            requests,
+            readings,
+            refresh_ready,
+            repaint_ctx,
+            requested: None,
+            last_update: Instant::now(),
+        }
+    }
+
+    /// Keys handled in `show`, with their help text; each action is a change in percent
+    fn key_table(&self) -> Vec<Shortcut<i32>> {
+        let label = if self.options.arrow_keys { "↑ / ↓" } else { "↑ / ↓ over brightness" };
+        vec![Shortcut::new(
+            label,
+            format!("Brightness up / down {}%", STEP),
+            vec![(Key::ArrowUp, STEP), (Key::ArrowDown, -STEP)],
+        )]
+    }
+
+    /// Sends a percentage the user picked to the worker
+    fn set_percent(&mut self, percent: i32) {
+        self.percent = Some(percent);
+        self.requested = Some(percent);
+        self.requests.send(BrightnessRequest::Set(percent)).ok();
+    }
+}
+
+impl Panel for BrightnessWidget {
+    fn next_update(&self) -> Option<Duration> {
+        Some(self.options.refresh_interval.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        (!self.dragging && self.last_update.elapsed() > self.options.refresh_interval)
+            || self.refresh_ready.load(Ordering::Acquire)
+    }
+
+    /// Applies readings from the worker, and asks for the next one when it's due
+    fn update(&mut self) {
+        self.refresh_ready.store(false, Ordering::Release);
+        while let Ok(reading) = self.readings.try_recv() {
+            // Taken before the latest change was applied; it would snap the slider back
+            if self.requested.is_some() && reading.after_set != self.requested {
+                continue;
+            }
+            self.requested = None;
+            self.loaded = true;
+            if !self.dragging {
+                self.percent = reading.percent;
+            }
+        }
+
+        if !self.dragging && self.last_update.elapsed() > self.options.refresh_interval {
+            self.requests.send(BrightnessRequest::Read).ok();
+            self.last_update = Instant::now();
+        }
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(BRIGHTNESS_WIDTH, BRIGHTNESS_HEIGHT)
+    }
+
+    fn margin(&self) -> f32 {
+        BRIGHTNESS_MARGIN
+    }
+
+    fn shortcuts(&self) -> Vec<(String, String)> {
+        self.key_table().iter().map(Shortcut::help).collect()
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        // Readings finishing later need a context to wake
+        self.repaint_ctx.get_or_init(|| ui.ctx().clone());
+
+        let Some(mut percent) = self.percent else {
+            ui.horizontal(|ui| {
+                ui.set_width(BRIGHTNESS_WIDTH - 2.0 * BRIGHTNESS_MARGIN);
+                ui.set_height(BRIGHTNESS_HEIGHT - 2.0 * BRIGHTNESS_MARGIN);
+                ui.label(RichText::new(egui_phosphor::regular::SUN_DIM).color(self.colors.outline).size(22.0));
+                if self.loaded {
+                    ui.label(RichText::new("No backlight").color(self.colors.on_surface_variant).size(16.0));
+                }
+            });
+            return;
+        };
+        // Only input moves the brightness; a polled value is shown as it is
+        let mut target = percent;
+        let mut changed = false;
+
+        let response = ui.horizontal(|ui| {
+            ui.set_width(BRIGHTNESS_WIDTH - 2.0 * BRIGHTNESS_MARGIN);
+            ui.set_height(BRIGHTNESS_HEIGHT - 2.0 * BRIGHTNESS_MARGIN);
+
+            ui.label(RichText::new(egui_phosphor::regular::SUN).color(self.colors.primary_fixed_dim).size(22.0));
+            ui.scope(|ui| {
+                super::style_slider(ui, &self.colors);
+                ui.spacing_mut().slider_width = ui.available_width() - 48.0;
+                let response = ui.add(Slider::new(&mut percent, MIN_PERCENT..=100).show_value(false).trailing_fill(true));
+                self.dragging = response.dragged();
+                if response.changed() {
+                    target = percent;
+                    changed = true;
+                }
+            });
+            ui.label(RichText::new(format!("{}%", target)).color(self.colors.on_surface_variant).size(14.0));
+        }).response;
+
+        // Scrolling anywhere over the widget steps the brightness
+        let hovered = ui.rect_contains_pointer(response.rect);
+        if hovered {
+            let scroll = ui.input(|i| i.raw_scroll_delta.y);
+            let settled = self.last_scroll_step.is_none_or(|at| at.elapsed() > SCROLL_DEBOUNCE);
+            if scroll != 0.0 && settled {
+                target += if scroll > 0.0 { STEP } else { -STEP };
+                changed = true;
+                self.last_scroll_step = Some(Instant::now());
+            }
+        }
+
+        // Typing into a text field elsewhere in the window doesn't change the brightness
+        if (self.options.arrow_keys || hovered) && !ui.ctx().wants_keyboard_input() {
+            let table = self.key_table();
+            let steps = ui.input(|i| shortcut::pressed(i, &table));
+            changed |= !steps.is_empty();
+            target += steps.into_iter().sum::<i32>();
+        }
+
+        if changed {
+            self.set_percent(target.clamp(MIN_PERCENT, 100));
+        }
+    }
+}
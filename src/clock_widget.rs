@@ -0,0 +1,177 @@
+use std::time::{Duration, Instant};
+
+use chrono::{
+    DateTime,
+    Datelike,
+    Local,
+    NaiveDate,
+};
+use eframe::egui::{
+    Align,
+    Align2,
+    CursorIcon,
+    FontId,
+    Layout,
+    Rect,
+    RichText,
+    Sense,
+    Ui,
+    Vec2,
+};
+
+use super::panel::Panel;
+
+/// How often the time is read, so seconds in --clock-format stay current
+const REFRESH: Duration = Duration::from_secs(1);
+const CLOCK_MARGIN: f32 = 8.0;
+/// Width of the widget
+const CLOCK_WIDTH: f32 = 260.0;
+/// Height of the collapsed widget, including the frame padding
+const CLOCK_HEIGHT: f32 = 48.0;
+/// Height of the month title and weekday names above the grid
+const CALENDAR_HEADER_HEIGHT: f32 = 56.0;
+/// Size of one day in the calendar grid
+const CELL_SIZE: Vec2 = Vec2::new(32.0, 26.0);
+/// Weeks a month can touch
+const MAX_WEEKS: usize = 6;
+/// Height of the expanded widget
+const CLOCK_MAX_HEIGHT: f32 = CLOCK_HEIGHT + CALENDAR_HEADER_HEIGHT + CELL_SIZE.y * MAX_WEEKS as f32;
+/// strftime format of the date beside the time
+const DATE_FORMAT: &str = "%a %-d %b";
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+/// Options for the clock widget
+#[derive(Debug, Clone)]
+pub struct ClockOptions {
+    /// strftime format of the time, already checked by `parse_clock_format`
+    pub format: String,
+}
+
+/// Current time and date, with a month calendar when expanded
+pub struct ClockWidget {
+    colors: super::Colors,
+    options: ClockOptions,
+    now: DateTime<Local>,
+    expanded: bool,
+    last_update: Instant,
+    size: Vec2,
+}
+
+impl ClockWidget {
+    pub fn new(colors: super::Colors, options: ClockOptions) -> Self {
+        Self {
+            colors,
+            options,
+            now: Local::now(),
+            expanded: false,
+            last_update: Instant::now(),
+            size: Vec2::new(CLOCK_WIDTH, CLOCK_HEIGHT),
+        }
+    }
+
+    /// Month grid with today highlighted; weeks start on Monday
+    fn show_calendar(&self, ui: &mut Ui) {
+        let today = self.now.date_naive();
+        let Some(first) = NaiveDate::from_ymd_opt(today.year(), today.month(), 1) else {
+            return;
+        };
+        ui.vertical_centered(|ui| {
+            ui.label(RichText::new(today.format("%B %Y").to_string()).color(self.colors.on_surface_variant).size(14.0));
+            let (rect, _) = ui.allocate_exact_size(
+                Vec2::new(CELL_SIZE.x * 7.0, CELL_SIZE.y * (MAX_WEEKS + 1) as f32),
+                Sense::hover(),
+            );
+            self.paint_month(ui, rect, today, first);
+        });
+    }
+
+    /// Paints weekday names and the days of `first`'s month into `rect`
+    fn paint_month(&self, ui: &Ui, rect: Rect, today: NaiveDate, first: NaiveDate) {
+        let offset = first.weekday().num_days_from_monday() as usize;
+        let days = first
+            .iter_days()
+            .take_while(|day| day.month() == first.month())
+            .count();
+        let painter = ui.painter();
+        let font = FontId::proportional(13.0);
+
+        for (column, name) in WEEKDAYS.iter().enumerate() {
+            let center = rect.min + Vec2::new((column as f32 + 0.5) * CELL_SIZE.x, CELL_SIZE.y / 2.0);
+            painter.text(center, Align2::CENTER_CENTER, name, font.clone(), self.colors.outline);
+        }
+
+        for day in 1..=days {
+            let cell = offset + day - 1;
+            let center = rect.min + Vec2::new(
+                ((cell % 7) as f32 + 0.5) * CELL_SIZE.x,
+                ((cell / 7 + 1) as f32 + 0.5) * CELL_SIZE.y,
+            );
+            let color = if day as u32 == today.day() {
+                painter.circle_filled(center, CELL_SIZE.y / 2.0 - 1.0, self.colors.primary_fixed_dim);
+                self.colors.on_primary_fixed
+            } else {
+                self.colors.on_surface_variant
+            };
+            painter.text(center, Align2::CENTER_CENTER, day.to_string(), font.clone(), color);
+        }
+    }
+}
+
+impl Panel for ClockWidget {
+    fn next_update(&self) -> Option<Duration> {
+        Some(REFRESH.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        self.last_update.elapsed() >= REFRESH
+    }
+
+    fn update(&mut self) {
+        self.now = Local::now();
+        self.last_update = Instant::now();
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    fn max_size(&self) -> Vec2 {
+        Vec2::new(CLOCK_WIDTH, CLOCK_MAX_HEIGHT)
+    }
+
+    fn margin(&self) -> f32 {
+        CLOCK_MARGIN
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        let response = ui.vertical(|ui| {
+            ui.set_width(CLOCK_WIDTH - 2.0 * CLOCK_MARGIN);
+
+            ui.horizontal(|ui| {
+                ui.set_height(CLOCK_HEIGHT - 2.0 * CLOCK_MARGIN);
+                ui.label(RichText::new(egui_phosphor::regular::CLOCK).color(self.colors.primary_fixed_dim).size(22.0));
+                ui.label(RichText::new(self.now.format(&self.options.format).to_string()).color(self.colors.primary_fixed_dim).size(18.0));
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    ui.label(RichText::new(self.now.format(DATE_FORMAT).to_string()).color(self.colors.on_surface_variant).size(14.0));
+                });
+            });
+            if self.expanded {
+                self.show_calendar(ui);
+            }
+        })
+        .response;
+
+        // Click anywhere on the panel to show or hide the calendar
+        let response = response.interact(Sense::click()).on_hover_cursor(CursorIcon::PointingHand);
+        if response.clicked() {
+            self.expanded = !self.expanded;
+        }
+
+        let height = if self.expanded { CLOCK_MAX_HEIGHT } else { CLOCK_HEIGHT };
+        self.size = Vec2::new(CLOCK_WIDTH, height);
+    }
+}
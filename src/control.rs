@@ -0,0 +1,254 @@
+use eframe::egui::Context;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Socket name inside $XDG_RUNTIME_DIR
+const SOCKET_NAME: &str = "hypowertools.sock";
+/// How long either end waits on a silent peer
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Widgets a daemon can show
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Widget {
+    Workspaces,
+    Overview,
+    Network,
+    Battery,
+    Volume,
+    Brightness,
+    Bluetooth,
+    Clock,
+    Media,
+    SysMon,
+    Keyboard,
+    Power,
+}
+
+impl Widget {
+    pub fn name(self) -> &'static str {
+        match self {
+            Widget::Workspaces => "workspaces",
+            Widget::Overview => "overview",
+            Widget::Network => "network",
+            Widget::Battery => "battery",
+            Widget::Volume => "volume",
+            Widget::Brightness => "brightness",
+            Widget::Bluetooth => "bluetooth",
+            Widget::Clock => "clock",
+            Widget::Media => "media",
+            Widget::SysMon => "sysmon",
+            Widget::Keyboard => "keyboard",
+            Widget::Power => "power",
+        }
+    }
+}
+
+impl std::str::FromStr for Widget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "workspaces" => Ok(Widget::Workspaces),
+            "overview" => Ok(Widget::Overview),
+            "network" => Ok(Widget::Network),
+            "battery" => Ok(Widget::Battery),
+            "volume" => Ok(Widget::Volume),
+            "brightness" => Ok(Widget::Brightness),
+            "bluetooth" => Ok(Widget::Bluetooth),
+            "clock" => Ok(Widget::Clock),
+            "media" => Ok(Widget::Media),
+            "sysmon" => Ok(Widget::SysMon),
+            "keyboard" => Ok(Widget::Keyboard),
+            "power" => Ok(Widget::Power),
+            _ => Err(format!("Invalid widget: {}", s)),
+        }
+    }
+}
+
+/// A request sent over the control socket, one per line
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    /// Shows the widgets, replacing whatever was shown
+    Show(Vec<Widget>),
+    /// Hides the widgets if exactly these are shown, otherwise shows them
+    Toggle(Vec<Widget>),
+    Hide,
+    /// Stops the daemon
+    Quit,
+}
+
+impl std::str::FromStr for ControlCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let verb = words.next().unwrap_or_default().to_lowercase();
+        let widgets = words.map(str::parse).collect::<Result<Vec<Widget>, _>>()?;
+
+        match (verb.as_str(), widgets.is_empty()) {
+            ("show", false) => Ok(ControlCommand::Show(widgets)),
+            ("toggle", false) => Ok(ControlCommand::Toggle(widgets)),
+            ("show" | "toggle", true) => Err(format!("{} needs at least one widget", verb)),
+            ("hide", true) => Ok(ControlCommand::Hide),
+            ("quit", true) => Ok(ControlCommand::Quit),
+            ("hide" | "quit", false) => Err(format!("{} takes no widgets", verb)),
+            _ => Err(format!("Invalid command: {}", s.trim())),
+        }
+    }
+}
+
+impl fmt::Display for ControlCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (verb, widgets) = match self {
+            ControlCommand::Show(widgets) => ("show", widgets.as_slice()),
+            ControlCommand::Toggle(widgets) => ("toggle", widgets.as_slice()),
+            ControlCommand::Hide => ("hide", &[][..]),
+            ControlCommand::Quit => ("quit", &[][..]),
+        };
+        write!(f, "{}", verb)?;
+        for widget in widgets {
+            write!(f, " {}", widget.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// Where the daemon listens
+pub fn socket_path() -> PathBuf {
+    let runtime_dir = env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(runtime_dir).join(SOCKET_NAME)
+}
+
+/// Connects to a running daemon, if there is one
+pub fn connect() -> Option<UnixStream> {
+    UnixStream::connect(socket_path()).ok()
+}
+
+/// Sends `command` over `stream` and waits for the daemon's reply
+pub fn send(mut stream: UnixStream, command: &ControlCommand) -> Result<(), String> {
+    stream.set_read_timeout(Some(SOCKET_TIMEOUT)).ok();
+    writeln!(stream, "{}", command).map_err(|err| err.to_string())?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).map_err(|err| err.to_string())?;
+    match reply.trim() {
+        "ok" => Ok(()),
+        reply => Err(reply.strip_prefix("error: ").unwrap_or(reply).to_string()),
+    }
+}
+
+/// Listening end of the control socket; the socket file is removed on drop
+pub struct ControlSocket {
+    path: PathBuf,
+    listener: Option<UnixListener>,
+    sender: Sender<ControlCommand>,
+    commands: Receiver<ControlCommand>,
+}
+
+impl ControlSocket {
+    /// Binds the socket, taking over one left behind by a daemon that crashed
+    pub fn bind() -> io::Result<Self> {
+        let path = socket_path();
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(err) if err.kind() == ErrorKind::AddrInUse => {
+                if UnixStream::connect(&path).is_ok() {
+                    return Err(io::Error::new(ErrorKind::AddrInUse, "another daemon is already running"));
+                }
+                fs::remove_file(&path)?;
+                UnixListener::bind(&path)?
+            }
+            Err(err) => return Err(err),
+        };
+
+        let (sender, commands) = mpsc::channel();
+        Ok(Self { path, listener: Some(listener), sender, commands })
+    }
+
+    /// Starts accepting commands on a background thread, waking `ctx` for each one
+    ///
+    /// `wake` runs on that thread after each command is acknowledged, before the
+    /// repaint is requested. A hidden window gets no frames from the compositor,
+    /// so anything needed to make it render again has to happen there.
+    pub fn listen(&mut self, ctx: Context, wake: impl Fn(&ControlCommand) + Send + 'static) {
+        let Some(listener) = self.listener.take() else {
+            return;
+        };
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                stream.set_read_timeout(Some(SOCKET_TIMEOUT)).ok();
+
+                let mut line = String::new();
+                if BufReader::new(&stream).read_line(&mut line).is_err() {
+                    continue;
+                }
+                let command = match line.parse::<ControlCommand>() {
+                    Ok(command) => command,
+                    Err(err) => {
+                        writeln!(&stream, "error: {}", err).ok();
+                        continue;
+                    }
+                };
+                if sender.send(command.clone()).is_err() {
+                    break;
+                }
+                writeln!(&stream, "ok").ok();
+                wake(&command);
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// Next command received since the last call
+    pub fn try_recv(&self) -> Option<ControlCommand> {
+        self.commands.try_recv().ok()
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_round_trip_through_display() {
+        let commands = [
+            ControlCommand::Show(vec![Widget::Network, Widget::SysMon]),
+            ControlCommand::Toggle(vec![Widget::Overview]),
+            ControlCommand::Hide,
+            ControlCommand::Quit,
+        ];
+        for command in commands {
+            assert_eq!(command.to_string().parse::<ControlCommand>(), Ok(command.clone()));
+        }
+    }
+
+    #[test]
+    fn parses_case_and_spacing_loosely() {
+        assert_eq!("  SHOW  Clock\tmedia ".parse(), Ok(ControlCommand::Show(vec![Widget::Clock, Widget::Media])));
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        for input in ["", "show", "toggle", "hide network", "quit now", "open clock", "show clock weather"] {
+            assert!(input.parse::<ControlCommand>().is_err(), "{:?}", input);
+        }
+    }
+}
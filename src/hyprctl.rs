@@ -0,0 +1,28 @@
+use std::ffi::OsStr;
+use std::process::{Command, Output};
+use std::sync::Once;
+
+/// Guards the warning printed when hyprctl can't be started
+static MISSING_WARNING: Once = Once::new();
+
+/// Runs `hyprctl` with `args`, warning once if the binary can't be started
+///
+/// A non-zero exit is still returned, so callers can look at the output.
+pub fn run<I, S>(args: I) -> Option<Output>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    match Command::new("hyprctl").args(args).output() {
+        Ok(output) => Some(output),
+        Err(err) => {
+            MISSING_WARNING.call_once(|| eprintln!("Warning: failed to run hyprctl: {}", err));
+            None
+        }
+    }
+}
+
+/// Whether hyprctl is installed and can be started
+pub fn is_available() -> bool {
+    Command::new("hyprctl").arg("version").output().is_ok()
+}
@@ -0,0 +1,179 @@
+use std::time::{Duration, Instant};
+
+use eframe::egui::{
+    CursorIcon,
+    Label,
+    RichText,
+    Sense,
+    Ui,
+    Vec2,
+};
+use serde::Deserialize;
+
+use super::hyprctl;
+use super::panel::Panel;
+
+/// How often the layout is read unless --refresh-ms says otherwise
+pub const DEFAULT_REFRESH: Duration = Duration::from_millis(1000);
+const KEYBOARD_MARGIN: f32 = 8.0;
+const KEYBOARD_WIDTH: f32 = 220.0;
+const KEYBOARD_HEIGHT: f32 = 48.0;
+
+/// Options for the keyboard layout widget
+#[derive(Debug, Clone)]
+pub struct KeyboardOptions {
+    /// How often the layout is read
+    pub refresh_interval: Duration,
+}
+
+/// Keyboard as listed by `hyprctl devices -j`
+#[derive(Deserialize, Debug, Clone)]
+struct Keyboard {
+    name: String,
+    /// Comma separated XKB layouts, e.g. `us,de`
+    #[serde(default)]
+    layout: String,
+    /// Human readable name of the active layout, e.g. `German`
+    active_keymap: String,
+    /// Index into `layout`; older Hyprland versions don't report it
+    #[serde(default)]
+    active_layout_index: Option<usize>,
+    /// Whether this is the keyboard Hyprland takes the layout from
+    #[serde(default)]
+    main: bool,
+}
+
+impl Keyboard {
+    /// Short name of the active layout, like `US`
+    fn abbreviation(&self) -> String {
+        self.active_layout_index
+            .and_then(|index| self.layout.split(',').nth(index))
+            .map(str::trim)
+            .filter(|layout| !layout.is_empty())
+            .map_or_else(
+                || self.active_keymap.chars().take(2).collect::<String>(),
+                str::to_string,
+            )
+            .to_uppercase()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Devices {
+    #[serde(default)]
+    keyboards: Vec<Keyboard>,
+}
+
+/// The main keyboard, or the first one when none is marked main
+fn get_keyboard() -> Option<Keyboard> {
+    let output = hyprctl::run(["devices", "-j"])?;
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let devices = serde_json::from_str::<Devices>(&stdout).ok()?;
+    let main = devices.keyboards.iter().position(|keyboard| keyboard.main).unwrap_or(0);
+    devices.keyboards.into_iter().nth(main)
+}
+
+/// Active keyboard layout; click to switch to the next one
+pub struct KeyboardWidget {
+    colors: super::Colors,
+    options: KeyboardOptions,
+    keyboard: Option<Keyboard>,
+    last_update: Instant,
+}
+
+impl KeyboardWidget {
+    pub fn new(colors: super::Colors, options: KeyboardOptions) -> Self {
+        let mut widget = Self {
+            colors,
+            options,
+            keyboard: None,
+            last_update: Instant::now(),
+        };
+        widget.update();
+        widget
+    }
+}
+
+impl Panel for KeyboardWidget {
+    fn next_update(&self) -> Option<Duration> {
+        Some(self.options.refresh_interval.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        self.last_update.elapsed() > self.options.refresh_interval
+    }
+
+    fn update(&mut self) {
+        self.keyboard = get_keyboard();
+        self.last_update = Instant::now();
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(KEYBOARD_WIDTH, KEYBOARD_HEIGHT)
+    }
+
+    fn margin(&self) -> f32 {
+        KEYBOARD_MARGIN
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        let response = ui.horizontal(|ui| {
+            ui.set_width(KEYBOARD_WIDTH - 2.0 * KEYBOARD_MARGIN);
+            ui.set_height(KEYBOARD_HEIGHT - 2.0 * KEYBOARD_MARGIN);
+
+            let Some(keyboard) = &self.keyboard else {
+                ui.label(RichText::new(egui_phosphor::regular::KEYBOARD).color(self.colors.outline).size(22.0));
+                ui.label(RichText::new("No keyboard").color(self.colors.on_surface_variant).size(16.0));
+                return;
+            };
+
+            ui.label(RichText::new(egui_phosphor::regular::KEYBOARD).color(self.colors.primary_fixed_dim).size(22.0));
+            ui.label(RichText::new(keyboard.abbreviation()).color(self.colors.primary_fixed_dim).size(16.0).strong());
+            ui.add(Label::new(RichText::new(&keyboard.active_keymap).color(self.colors.on_surface_variant).size(14.0)).truncate());
+        })
+        .response;
+
+        // Click anywhere on the panel to switch to the next layout
+        let response = response.interact(Sense::click());
+        let Some(keyboard) = &self.keyboard else {
+            return;
+        };
+        if response.on_hover_cursor(CursorIcon::PointingHand).on_hover_text("Next layout").clicked() {
+            hyprctl::run(["switchxkblayout", keyboard.name.as_str(), "next"]);
+            self.update();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyboard(layout: &str, active_keymap: &str, active_layout_index: Option<usize>) -> Keyboard {
+        Keyboard {
+            name: "at-translated-set-2-keyboard".to_string(),
+            layout: layout.to_string(),
+            active_keymap: active_keymap.to_string(),
+            active_layout_index,
+            main: true,
+        }
+    }
+
+    #[test]
+    fn abbreviates_the_active_layout() {
+        assert_eq!(keyboard("us,de", "German", Some(1)).abbreviation(), "DE");
+        assert_eq!(keyboard("us, fr", "French", Some(1)).abbreviation(), "FR");
+    }
+
+    #[test]
+    fn falls_back_to_the_keymap_name() {
+        // Older Hyprland versions don't report the index
+        assert_eq!(keyboard("us,de", "German", None).abbreviation(), "GE");
+        assert_eq!(keyboard("us", "English (US)", Some(3)).abbreviation(), "EN");
+        assert_eq!(keyboard("", "English (US)", Some(0)).abbreviation(), "EN");
+    }
+}
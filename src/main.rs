@@ -1,56 +1,390 @@
-use eframe::egui::{CentralPanel, Context, ViewportBuilder, Frame, Color32, Margin, Rounding, Key, ViewportCommand, Vec2};
-use clap::Parser;
-use std::fs;
-use shellexpand;
-use serde_json;
-use std::process::Command;
+use eframe::egui::{CentralPanel, Context, ViewportBuilder, Frame, Color32, Margin, CornerRadius, Key, ViewportCommand, Vec2, Area, Id, Order, ScrollArea, Grid, RichText};
+use clap::{CommandFactory, Parser};
+use log::{debug, info, warn};
+use chrono::format::{Item, StrftimeItems};
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 mod workspace_switcher;
 mod network_widget;
-use workspace_switcher::WorkspaceSwitcher;
-use network_widget::NetworkWidget;
+mod battery_widget;
+mod volume_widget;
+mod brightness_widget;
+mod bluetooth_widget;
+mod clock_widget;
+mod media_widget;
+mod sysmon_widget;
+mod keyboard_widget;
+mod power_widget;
+mod control;
+mod hyprctl;
+mod panel;
+mod shortcut;
+use workspace_switcher::{WorkspaceSwitcher, SwitcherOptions, ClosePolicy, get_active_monitor, find_monitor, ICON_OVERRIDES_PATH};
+use battery_widget::{BatteryWidget, BatteryOptions};
+use volume_widget::{VolumeWidget, VolumeOptions};
+use brightness_widget::{BrightnessWidget, BrightnessOptions};
+use bluetooth_widget::{BluetoothWidget, BluetoothOptions};
+use clock_widget::{ClockWidget, ClockOptions};
+use media_widget::{MediaWidget, MediaOptions};
+use sysmon_widget::{SysMonWidget, SysMonOptions};
+use keyboard_widget::{KeyboardWidget, KeyboardOptions};
+use power_widget::{PowerWidget, PowerOptions};
+use control::{ControlCommand, ControlSocket, Widget};
+use panel::Panel;
+use shortcut::Shortcut;
+use network_widget::{NetworkWidget, NetworkOptions, NetworkLayout, NetworkSort, SignalStyle};
 
 /// Application identifier for window manager
 const APP_ID: &str = "hypowertools";
-/// Path to the colors configuration file
+/// Option defaults, keyed like the long command line flags
+const CONFIG_PATH: &str = "~/.config/hypowertools/config.toml";
+/// Default path of the colors configuration file
 const COLORS_CONFIG_PATH: &str = "~/.config/hypr/hyprland/colors.conf";
+/// Where --remember-size keeps the switcher's user-chosen size
+const SIZE_CACHE_PATH: &str = "~/.cache/hypowertools/switcher-size";
+/// How many frames look for our window before positioning gives up
+const MAX_POSITION_ATTEMPTS: i32 = 5;
+/// Gap between widgets stacked in one window
+const STACK_SPACING: f32 = 8.0;
+/// Length of the fade-out before the window closes
+const FADE_OUT: Duration = Duration::from_millis(120);
+/// Length of the fade-in once the window has been positioned
+const FADE_IN: Duration = Duration::from_millis(150);
+/// Compositor-driven resizes right after positioning aren't treated as the user's
+const RESIZE_GRACE: Duration = Duration::from_secs(1);
 
 /// Command line arguments for the application
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[derive(Parser, Debug, Clone)]
+// Flags may appear twice, once from config.toml and once on the command line
+#[command(author, version, about, long_about = None, args_override_self = true)]
 struct Args {
     /// Show workspace switcher widget
     #[arg(long)]
     workspaces: bool,
 
+    /// Workspace id to pre-highlight in the switcher; Enter switches to it
+    #[arg(long)]
+    select: Option<i32>,
+
+    /// Hyprland dispatcher that opens a full workspace overview
+    #[arg(long, default_value = "hyprexpo:expo toggle")]
+    overview_command: String,
+
+    /// Key in the switcher that runs --overview-command
+    #[arg(long, default_value = "E", value_parser = parse_key)]
+    overview_key: Key,
+
+    /// Dimming of workspace background images, from 0.0 (none) to 1.0 (black)
+    #[arg(long, default_value = "0.47", value_parser = parse_fraction)]
+    bg_dim: f32,
+
+    /// Surface-color tint over workspace background images, from 0.0 to 1.0
+    #[arg(long, default_value = "0.3", value_parser = parse_fraction)]
+    bg_tint: f32,
+
+    /// Use solid-color workspace buttons instead of the wallpaper
+    #[arg(long)]
+    no_background: bool,
+
+    /// Mark icons of XWayland windows with an "X11" badge
+    #[arg(long)]
+    mark_xwayland: bool,
+
+    /// Keep the switcher open after switching, even with number keys
+    #[arg(long, overrides_with = "once")]
+    keep_open: bool,
+
+    /// Close the switcher after any workspace switch, including clicks
+    #[arg(long, overrides_with = "keep_open")]
+    once: bool,
+
+    /// Show one row per monitor with its active workspace
+    #[arg(long)]
+    overview: bool,
+
     /// Show network widget
     #[arg(long)]
     network: bool,
 
-    /// Position of the widget (center, top, top-left, top-right, bottom, bottom-left, bottom-right)
+    /// Show battery widget
+    #[arg(long)]
+    battery: bool,
+
+    /// Show volume widget
+    #[arg(long)]
+    volume: bool,
+
+    /// Show brightness widget
+    #[arg(long)]
+    brightness: bool,
+
+    /// Show Bluetooth widget
+    #[arg(long)]
+    bluetooth: bool,
+
+    /// Show clock widget
+    #[arg(long)]
+    clock: bool,
+
+    /// strftime format of the clock widget's time, e.g. %H:%M:%S or %-I:%M %p
+    #[arg(long, default_value = "%H:%M", value_parser = parse_clock_format)]
+    clock_format: String,
+
+    /// Show media widget
+    #[arg(long)]
+    media: bool,
+
+    /// Show system monitor widget
+    #[arg(long)]
+    sysmon: bool,
+
+    /// Show keyboard layout widget
+    #[arg(long)]
+    keyboard: bool,
+
+    /// Show power menu widget
+    #[arg(long)]
+    power: bool,
+
+    /// Shell command run by the power menu's lock button
+    #[arg(long, default_value = "hyprlock")]
+    power_lock_cmd: String,
+
+    /// Shell command run by the power menu's logout button
+    #[arg(long, default_value = "hyprctl dispatch exit")]
+    power_logout_cmd: String,
+
+    /// Shell command run by the power menu's suspend button
+    #[arg(long, default_value = "systemctl suspend")]
+    power_suspend_cmd: String,
+
+    /// Shell command run by the power menu's reboot button
+    #[arg(long, default_value = "systemctl reboot")]
+    power_reboot_cmd: String,
+
+    /// Shell command run by the power menu's shutdown button
+    #[arg(long, default_value = "systemctl poweroff")]
+    power_shutdown_cmd: String,
+
+    /// Position of the widget (center, top, top-left, top-right, bottom, bottom-left, bottom-right, or x,y)
     #[arg(long, default_value = "center")]
     position: Position,
 
-    /// Padding from top edge in pixels
-    #[arg(long, default_value = "20")]
-    padding_top: i32,
+    /// Edge padding preset (tight, comfortable, spacious)
+    #[arg(long, default_value = "comfortable")]
+    preset: Preset,
 
-    /// Padding from bottom edge in pixels
-    #[arg(long, default_value = "20")]
-    padding_bottom: i32,
+    /// Monitor to show the widget on, by name or id (defaults to the focused monitor)
+    #[arg(long)]
+    monitor: Option<String>,
+
+    /// Padding from top edge in pixels, or a percentage of the monitor height like 5% (defaults to the --preset value)
+    #[arg(long)]
+    padding_top: Option<Padding>,
+
+    /// Padding from bottom edge in pixels, or a percentage of the monitor height like 5% (defaults to the --preset value)
+    #[arg(long)]
+    padding_bottom: Option<Padding>,
+
+    /// Padding from left edge in pixels, or a percentage of the monitor width like 5% (defaults to the --preset value)
+    #[arg(long)]
+    padding_left: Option<Padding>,
 
-    /// Padding from left edge in pixels
-    #[arg(long, default_value = "20")]
-    padding_left: i32,
+    /// Padding from right edge in pixels, or a percentage of the monitor width like 5% (defaults to the --preset value)
+    #[arg(long)]
+    padding_right: Option<Padding>,
 
-    /// Padding from right edge in pixels
-    #[arg(long, default_value = "20")]
-    padding_right: i32,
+    /// Command launched on a workspace when its button is middle-clicked
+    #[arg(long, default_value = "kitty")]
+    middle_click_command: String,
+
+    /// Number workspace buttons 1..n by position, still switching to the real workspace
+    #[arg(long)]
+    remap_indices: bool,
+
+    /// Only list the workspaces of the monitor the switcher is shown on
+    #[arg(long)]
+    monitor_workspaces: bool,
+
+    /// Leave out workspaces without windows, except the current one
+    #[arg(long)]
+    hide_empty: bool,
+
+    /// App icons shown per workspace button before the rest are summed up as +N
+    #[arg(long, default_value = "3", value_parser = parse_max_icons)]
+    max_icons: usize,
+
+    /// Inset of the app icons from the workspace button's top-left corner
+    #[arg(long, default_value = "8")]
+    icon_margin: f32,
+
+    /// Inset of the workspace label from the button's bottom-left corner
+    #[arg(long, default_value = "8")]
+    label_margin: f32,
+
+    /// Network widget layout (list, or row for embedding in a bar)
+    #[arg(long, default_value = "list")]
+    network_layout: NetworkLayout,
+
+    /// Send a desktop notification when the network connection changes
+    #[arg(long)]
+    notify: bool,
+
+    /// Signal strength indicator style for the network widget (glyph, bars, ring)
+    #[arg(long, default_value = "glyph")]
+    signal_style: SignalStyle,
+
+    /// Show text labels on the network widget's action buttons
+    #[arg(long)]
+    labeled_buttons: bool,
+
+    /// Order of the network list below the connected network (signal, name)
+    #[arg(long, default_value = "signal")]
+    network_sort: NetworkSort,
+
+    /// Hide available networks with a weaker signal than this (0-100); known networks always show
+    #[arg(long, default_value = "0")]
+    min_signal: i32,
+
+    /// After connecting, check for a captive portal over HTTP and offer to open its login page
+    #[arg(long)]
+    captive_check: bool,
+
+    /// Size the network list to the widest visible SSID instead of a fixed width
+    #[arg(long)]
+    network_autowidth: bool,
+
+    /// Show download and upload rates of the connected interface in the network widget
+    #[arg(long)]
+    network_throughput: bool,
+
+    /// Command offered to start NetworkManager when it isn't running
+    #[arg(long, default_value = "systemctl start NetworkManager")]
+    nm_start_command: String,
+
+    /// Don't pin the widget, so it stays on the workspace it was opened on
+    #[arg(long)]
+    no_pin: bool,
+
+    /// Use Hyprland's active border color as the accent color
+    #[arg(long)]
+    use_hypr_accent: bool,
+
+    /// Color theme (dark, light); colors.conf still overrides its fallback palette
+    #[arg(long, default_value = "dark")]
+    theme: Theme,
+
+    /// Colors config to read the palette and wallpaper from
+    #[arg(long, default_value = COLORS_CONFIG_PATH)]
+    colors_config: String,
+
+    /// Override the surface_container_low color (e.g. rgba(b9c3ffff) or #b9c3ff)
+    #[arg(long, value_name = "COLOR", value_parser = parse_color_arg)]
+    color_surface_container_low: Option<Color32>,
+
+    /// Override the surface_container_high color (e.g. rgba(b9c3ffff) or #b9c3ff)
+    #[arg(long, value_name = "COLOR", value_parser = parse_color_arg)]
+    color_surface_container_high: Option<Color32>,
+
+    /// Override the on_surface_variant color (e.g. rgba(b9c3ffff) or #b9c3ff)
+    #[arg(long, value_name = "COLOR", value_parser = parse_color_arg)]
+    color_on_surface_variant: Option<Color32>,
+
+    /// Override the on_primary_fixed color (e.g. rgba(b9c3ffff) or #b9c3ff)
+    #[arg(long, value_name = "COLOR", value_parser = parse_color_arg)]
+    color_on_primary_fixed: Option<Color32>,
+
+    /// Override the primary_fixed_dim color (e.g. rgba(b9c3ffff) or #b9c3ff)
+    #[arg(long, value_name = "COLOR", value_parser = parse_color_arg)]
+    color_primary_fixed_dim: Option<Color32>,
+
+    /// Override the surface color (e.g. rgba(b9c3ffff) or #b9c3ff)
+    #[arg(long, value_name = "COLOR", value_parser = parse_color_arg)]
+    color_surface: Option<Color32>,
+
+    /// Override the surface_container color (e.g. rgba(b9c3ffff) or #b9c3ff)
+    #[arg(long, value_name = "COLOR", value_parser = parse_color_arg)]
+    color_surface_container: Option<Color32>,
+
+    /// Override the outline color (e.g. rgba(b9c3ffff) or #b9c3ff)
+    #[arg(long, value_name = "COLOR", value_parser = parse_color_arg)]
+    color_outline: Option<Color32>,
+
+    /// Print the resolved color palette and exit
+    #[arg(long)]
+    check_colors: bool,
+
+    /// Write the resolved color palette to a colors.conf file and exit
+    #[arg(long, value_name = "PATH")]
+    export_colors: Option<String>,
+
+    /// Print version, detected tools and config paths as JSON and exit
+    #[arg(long)]
+    version_json: bool,
+
+    /// Override the UI scale factor (pixels per point)
+    #[arg(long, value_parser = parse_scale)]
+    scale: Option<f32>,
+
+    /// Skip decorative animations
+    #[arg(long)]
+    reduce_motion: bool,
+
+    /// Show and close the window without fading
+    #[arg(long)]
+    no_animation: bool,
+
+    /// Close the widget when its window loses focus
+    #[arg(long)]
+    close_on_unfocus: bool,
+
+    /// Focus an already running instance of this widget instead of opening another
+    #[arg(long)]
+    single_instance: bool,
+
+    /// Stay running with a hidden window, showing widgets on request over a control socket
+    #[arg(long, conflicts_with = "single_instance")]
+    daemon: bool,
+
+    /// Log positioning and daemon diagnostics to stderr (RUST_LOG gives finer control)
+    #[arg(long)]
+    verbose: bool,
+
+    /// Send a command (show <widgets>, toggle <widgets>, hide, quit) to a running daemon
+    #[arg(long, value_name = "COMMAND")]
+    send: Option<ControlCommand>,
+
+    /// Keep the switcher at the size it was last resized to instead of auto-sizing
+    #[arg(long)]
+    remember_size: bool,
+
+    /// How often widgets poll hyprctl/nmcli, in milliseconds (500 for workspaces, 1000 for network)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    refresh_ms: Option<u64>,
+
+    /// TTF/OTF font file to use for widget text
+    #[arg(long, value_name = "PATH")]
+    font: Option<String>,
+
+    /// Corner radius of widget panels and workspace buttons (0 for sharp corners)
+    #[arg(long, value_parser = parse_rounding)]
+    rounding: Option<f32>,
+
+    /// Maximum frames per second of the fade and switch animations (uncapped by
+    /// default); egui's own widget animations, such as hover effects and
+    /// spinners, are not capped
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    max_fps: Option<u32>,
 }
 
-#[derive(Parser, Debug, Clone)]
+#[derive(Debug, Clone)]
 enum Position {
     Center,
     Top,
@@ -59,6 +393,8 @@ enum Position {
     Bottom,
     BottomLeft,
     BottomRight,
+    /// Offset of the top-left corner from the monitor's origin
+    Exact(i32, i32),
 }
 
 impl std::str::FromStr for Position {
@@ -73,58 +409,518 @@ impl std::str::FromStr for Position {
             "bottom" => Ok(Position::Bottom),
             "bottom-left" => Ok(Position::BottomLeft),
             "bottom-right" => Ok(Position::BottomRight),
-            _ => Err(format!("Invalid position: {}", s)),
+            other => other.split_once(',')
+                .and_then(|(x, y)| Some(Position::Exact(x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                .ok_or_else(|| format!("Invalid position: {}", s)),
+        }
+    }
+}
+
+/// Curated edge paddings, overridable per side with --padding-*
+#[derive(Debug, Clone, Copy)]
+enum Preset {
+    Tight,
+    Comfortable,
+    Spacious,
+}
+
+impl Preset {
+    fn padding(self) -> i32 {
+        match self {
+            Preset::Tight => 8,
+            Preset::Comfortable => 20,
+            Preset::Spacious => 40,
         }
     }
 }
 
-/// Parses an RGBA color string in the format "rgba(rrggbbaa)"
+/// Distance from a monitor edge, absolute or relative to the monitor size
+#[derive(Debug, Clone, Copy)]
+enum Padding {
+    Pixels(i32),
+    Percent(f32),
+}
+
+impl Padding {
+    /// Resolves to pixels along a monitor dimension of `extent` pixels
+    fn resolve(self, extent: i32) -> i32 {
+        match self {
+            Padding::Pixels(pixels) => pixels,
+            Padding::Percent(percent) => (extent as f32 * percent / 100.0).round() as i32,
+        }
+    }
+}
+
+impl std::str::FromStr for Padding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_suffix('%') {
+            Some(percent) => match percent.trim().parse::<f32>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(Padding::Percent(percent)),
+                _ => Err(format!("Invalid padding: {} (percentages must be between 0% and 100%)", s)),
+            },
+            None => s.parse().map(Padding::Pixels).map_err(|_| format!("Invalid padding: {}", s)),
+        }
+    }
+}
+
+impl std::str::FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tight" => Ok(Preset::Tight),
+            "comfortable" => Ok(Preset::Comfortable),
+            "spacious" => Ok(Preset::Spacious),
+            _ => Err(format!("Invalid preset: {}", s)),
+        }
+    }
+}
+
+/// Base egui visuals and fallback palette
+#[derive(Debug, Clone, Copy)]
+enum Theme {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    fn visuals(self) -> eframe::egui::Visuals {
+        match self {
+            Theme::Dark => eframe::egui::Visuals::dark(),
+            Theme::Light => eframe::egui::Visuals::light(),
+        }
+    }
+}
+
+impl std::str::FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dark" => Ok(Theme::Dark),
+            "light" => Ok(Theme::Light),
+            _ => Err(format!("Invalid theme: {}", s)),
+        }
+    }
+}
+
+impl Args {
+    /// Shortest time between animation frames, from --max-fps
+    fn frame_interval(&self) -> Option<Duration> {
+        self.max_fps.map(|fps| Duration::from_secs(1) / fps)
+    }
+
+    /// Colors given on the command line, keyed like colors.conf
+    fn color_overrides(&self) -> Vec<(&'static str, Color32)> {
+        [
+            ("surface_container_low", self.color_surface_container_low),
+            ("surface_container_high", self.color_surface_container_high),
+            ("on_surface_variant", self.color_on_surface_variant),
+            ("on_primary_fixed", self.color_on_primary_fixed),
+            ("primary_fixed_dim", self.color_primary_fixed_dim),
+            ("surface", self.color_surface),
+            ("surface_container", self.color_surface_container),
+            ("outline", self.color_outline),
+        ]
+        .into_iter()
+        .filter_map(|(key, color)| color.map(|color| (key, color)))
+        .collect()
+    }
+}
+
+/// Options that pick what a run does, which config.toml may not set
+///
+/// Flags from the file can't be turned off again on the command line, so any of
+/// these would apply to every run. Widget flags are kept out the same way, matched
+/// by their `Widget` name instead of being listed here.
+const COMMAND_LINE_ONLY: &[&str] = &["daemon", "single_instance", "send", "check_colors", "export_colors", "version_json"];
+
+/// Reads config.toml and turns its entries into flags for `config_args`
+fn config_file_args() -> Vec<OsString> {
+    let path = shellexpand::tilde(CONFIG_PATH).to_string();
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    match toml::from_str::<toml::Table>(&content) {
+        Ok(table) => config_args(table, &path),
+        Err(err) => {
+            eprintln!("Warning: ignoring invalid {}: {}", path, err);
+            Vec::new()
+        }
+    }
+}
+
+/// Turns config file entries into flags placed before the real command line,
+/// so anything passed on the command line takes precedence
+fn config_args(table: toml::Table, path: &str) -> Vec<OsString> {
+    let command = Args::command();
+    let mut args = Vec::new();
+    for (key, value) in table {
+        let id = key.replace('-', "_");
+        if !command.get_arguments().any(|arg| arg.get_id() == id.as_str() && arg.get_long().is_some()) {
+            eprintln!("Warning: unknown option {} in {}", key, path);
+            continue;
+        }
+        if COMMAND_LINE_ONLY.contains(&id.as_str()) || id.parse::<Widget>().is_ok() {
+            eprintln!("Warning: ignoring {} in {}; it can only be given on the command line", key, path);
+            continue;
+        }
+
+        let flag = format!("--{}", id.replace('_', "-"));
+        match value {
+            toml::Value::Boolean(true) => args.push(OsString::from(flag)),
+            toml::Value::Boolean(false) => {}
+            toml::Value::String(value) => args.push(OsString::from(format!("{}={}", flag, value))),
+            toml::Value::Integer(value) => args.push(OsString::from(format!("{}={}", flag, value))),
+            toml::Value::Float(value) => args.push(OsString::from(format!("{}={}", flag, value))),
+            _ => eprintln!("Warning: unsupported value for {} in {}", key, path),
+        }
+    }
+    args
+}
+
+/// Parses a color argument in any format colors.conf accepts
+fn parse_color_arg(s: &str) -> Result<Color32, String> {
+    parse_rgba_color(s.trim()).ok_or_else(|| format!("Invalid color: {}", s))
+}
+
+/// Parses a positive UI scale factor
+fn parse_scale(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(scale) if scale > 0.0 && scale.is_finite() => Ok(scale),
+        _ => Err(format!("Invalid scale: {}", s)),
+    }
+}
+
+/// Checks a strftime format up front, since chrono panics on bad ones while formatting
+fn parse_clock_format(s: &str) -> Result<String, String> {
+    if StrftimeItems::new(s).any(|item| item == Item::Error) {
+        return Err(format!("Invalid clock format: {}", s));
+    }
+    Ok(s.to_string())
+}
+
+/// Parses a positive icon count
+fn parse_max_icons(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("Invalid icon count: {}", s)),
+    }
+}
+
+/// Parses a non-negative corner radius
+fn parse_rounding(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(radius) if radius >= 0.0 && radius.is_finite() => Ok(radius),
+        _ => Err(format!("Invalid rounding: {}", s)),
+    }
+}
+
+/// Parses a value between 0.0 and 1.0
+fn parse_fraction(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(value) if (0.0..=1.0).contains(&value) => Ok(value),
+        _ => Err(format!("Expected a value between 0.0 and 1.0: {}", s)),
+    }
+}
+
+/// Parses a key name as understood by egui (e.g. "E", "Space", "F2")
+fn parse_key(s: &str) -> Result<Key, String> {
+    Key::from_name(s).ok_or_else(|| format!("Invalid key: {}", s))
+}
+
+/// Formats a color the way colors.conf writes it, as "rgba(rrggbbaa)"
+fn format_rgba_color(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    format!("rgba({:02x}{:02x}{:02x}{:02x})", r, g, b, a)
+}
+
+/// Parses an RGBA color string in the format "rgba(rrggbbaa)", "rgb(rrggbb)", "#rrggbb" or "#rrggbbaa"
 fn parse_rgba_color(rgba_str: &str) -> Option<Color32> {
-    if rgba_str.starts_with("rgba(") && rgba_str.ends_with(")") {
-        let hex = rgba_str
-            .trim_start_matches("rgba(")
-            .trim_end_matches(")")
-            .trim();
+    if let Some(hex) = rgba_str.strip_prefix("rgb(").and_then(|rest| rest.strip_suffix(')')) {
+        let hex = hex.trim();
+        if !hex.is_ascii() || hex.len() != 6 {
+            return None;
+        }
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+        return Some(Color32::from_rgb(r, g, b));
+    }
+
+    if let Some(hex) = rgba_str.strip_prefix('#') {
+        if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+            return None;
+        }
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+        let a = match hex.get(6..8) {
+            Some(alpha) => u8::from_str_radix(alpha, 16).ok()?,
+            None => 0xff,
+        };
+        return Some(Color32::from_rgba_unmultiplied(r, g, b, a));
+    }
 
-        if hex.len() == 8 {
-            let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-            let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-            let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-            let a = u8::from_str_radix(&hex[6..8], 16).ok()?;
-            return Some(Color32::from_rgba_unmultiplied(r, g, b, a));
+    if let Some(hex) = rgba_str.strip_prefix("rgba(").and_then(|rest| rest.strip_suffix(')')) {
+        let hex = hex.trim();
+        if !hex.is_ascii() || hex.len() != 8 {
+            return None;
         }
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+        let a = u8::from_str_radix(&hex[6..8], 16).ok()?;
+        return Some(Color32::from_rgba_unmultiplied(r, g, b, a));
     }
     None
 }
 
-/// Reads color configuration from the config file
-fn read_colors_from_config() -> Option<Colors> {
-    let config_path = shellexpand::tilde(COLORS_CONFIG_PATH).to_string();
-    let content = fs::read_to_string(config_path).ok()?;
+/// Reads color configuration from the config file, falling back to the theme's
+/// default for each color the file lacks or that doesn't parse
+///
+/// Also returns the keys taken from the file. A missing file silently falls back
+/// to defaults; a file that exists but can't be read or lacks colors logs a
+/// warning first.
+fn read_colors_from_config(config_path: &str, theme: Theme) -> (Colors, Vec<&'static str>) {
+    let mut colors = Colors::defaults(theme);
+    let config_path = shellexpand::tilde(config_path).to_string();
+    let entries = match read_color_entries(&config_path) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return (colors, Vec::new()),
+        Err(err) => {
+            eprintln!("Warning: could not read {}: {}; using default colors", config_path, err);
+            return (colors, Vec::new());
+        }
+    };
+
+    if entries.is_empty() {
+        eprintln!("Warning: no color keys found in {}; using default colors", config_path);
+        return (colors, Vec::new());
+    }
+
+    let parsed: Vec<(&'static str, Color32)> = colors.fields().into_iter()
+        .filter_map(|(key, _)| Some((key, parse_rgba_color(entries.get(key)?)?)))
+        .collect();
+    if parsed.len() < colors.fields().len() {
+        eprintln!("Warning: {} is missing or has invalid color keys; using default colors for those", config_path);
+    }
+    colors.apply_overrides(&parsed);
+    (colors, parsed.into_iter().map(|(key, _)| key).collect())
+}
+
+/// Collects the `key = rgba(...)` entries from a colors config file
+fn read_color_entries(config_path: &str) -> std::io::Result<std::collections::HashMap<String, String>> {
+    let content = fs::read_to_string(config_path)?;
     let mut colors = std::collections::HashMap::new();
     
     for line in content.lines() {
         if let Some((key, value)) = line.split_once('=') {
             let key = key.trim().trim_start_matches('$');
             let value = value.trim();
-            if value.starts_with("rgba(") {
+            if value.starts_with("rgba(") || value.starts_with("rgb(") || value.starts_with('#') {
                 colors.insert(key.to_string(), value.to_string());
             }
         }
     }
-    
-    Some(Colors {
-        surface_container_low: parse_rgba_color(colors.get("surface_container_low")?)?,
-        surface_container_high: parse_rgba_color(colors.get("surface_container_high")?)?,
-        on_surface_variant: parse_rgba_color(colors.get("on_surface_variant")?)?,
-        on_primary_fixed: parse_rgba_color(colors.get("on_primary_fixed")?)?,
-        primary_fixed_dim: parse_rgba_color(colors.get("primary_fixed_dim")?)?,
-        surface: parse_rgba_color(colors.get("surface")?)?,
-        surface_container: parse_rgba_color(colors.get("surface_container")?)?,
-        outline: parse_rgba_color(colors.get("outline")?)?,
-    })
+    Ok(colors)
+}
+
+/// Writes the resolved palette to `path` in colors.conf format, for --export-colors
+fn export_colors(path: &str, config_path: &str, theme: Theme, overrides: &[(&str, Color32)]) -> std::io::Result<()> {
+    let colors = Colors::new(config_path, theme, overrides);
+    let config_path = shellexpand::tilde(config_path).to_string();
+
+    let mut content = String::from("# Generated by hypowertools --export-colors\n");
+    for (key, color) in colors.fields() {
+        content.push_str(&format!("${} = {}\n", key, format_rgba_color(color)));
+    }
+
+    // Keep the wallpaper setting so exporting over the live config doesn't drop it
+    if let Ok(existing) = fs::read_to_string(&config_path) {
+        let image = existing.lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(key, _)| key.trim().trim_start_matches('$') == "image");
+        if let Some((_, value)) = image {
+            content.push_str(&format!("$image = {}\n", value.trim()));
+        }
+    }
+
+    let path = shellexpand::tilde(path).to_string();
+    if let Some(parent) = Path::new(&path).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content)?;
+    println!("Exported colors to {}", path);
+    Ok(())
+}
+
+/// Prints the resolved palette and where it came from, for --check-colors
+fn check_colors(config_path: &str, theme: Theme, overrides: &[(&str, Color32)]) {
+    let config_path = shellexpand::tilde(config_path).to_string();
+    println!("Colors config: {}", config_path);
+
+    let (mut colors, from_config) = read_colors_from_config(&config_path, theme);
+    colors.apply_overrides(overrides);
+
+    println!();
+    for (key, color) in colors.fields() {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        let source = if overrides.iter().any(|(overridden, _)| *overridden == key) {
+            "override"
+        } else if from_config.contains(&key) {
+            "config"
+        } else {
+            "default"
+        };
+        println!(
+            "  {:<24} {}  r={:<3} g={:<3} b={:<3} a={:<3}  [{}]",
+            key, format_rgba_color(color), r, g, b, a, source
+        );
+    }
+
+    // Point out the entries that fell back to their default color
+    if let Ok(entries) = read_color_entries(&config_path) {
+        let mut problems: Vec<String> = entries.iter()
+            .filter(|(_, value)| parse_rgba_color(value).is_none())
+            .map(|(key, value)| format!("  {} = {} (unparseable)", key, value))
+            .collect();
+        problems.extend(colors.fields().iter()
+            .filter(|(key, _)| !entries.contains_key(*key))
+            .map(|(key, _)| format!("  {} (missing)", key)));
+        problems.sort();
+
+        if !problems.is_empty() {
+            println!();
+            println!("Problems:");
+            for problem in problems {
+                println!("{}", problem);
+            }
+        }
+    }
+}
+
+/// Lock held for the lifetime of a --single-instance widget
+///
+/// The lockfile is locked with flock rather than just created, so the kernel
+/// releases it when the holder exits, even after a crash. The file itself stays
+/// behind; removing it would let a later instance lock a fresh file while an
+/// older one still holds the unlinked one.
+struct InstanceLock {
+    /// None when the lockfile couldn't be opened, so nothing is held
+    _file: Option<fs::File>,
+}
+
+impl InstanceLock {
+    /// Takes the lock for the widget set `widgets` (e.g. `network+battery`) of the
+    /// app `app_id`, or returns the PID of the instance holding it, if it has written it yet
+    fn acquire(app_id: &str, widgets: &str) -> Result<Self, Option<u32>> {
+        let runtime_dir = env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
+        let path = PathBuf::from(runtime_dir).join(format!("{}-{}.lock", app_id, widgets));
+
+        let mut file = match OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path) {
+            Ok(file) => file,
+            Err(err) => {
+                // Unwritable runtime dir and the like; don't block startup
+                eprintln!("Warning: could not open lockfile {}: {}", path.display(), err);
+                return Ok(Self { _file: None });
+            }
+        };
+
+        match file.try_lock() {
+            Ok(()) => {
+                // Only the holder writes, so readers see its PID or nothing yet
+                file.set_len(0).ok();
+                write!(file, "{}", process::id()).ok();
+                Ok(Self { _file: Some(file) })
+            }
+            Err(TryLockError::WouldBlock) => {
+                let pid = fs::read_to_string(&path).ok().and_then(|pid| pid.trim().parse::<u32>().ok());
+                Err(pid)
+            }
+            Err(TryLockError::Error(err)) => {
+                eprintln!("Warning: could not lock {}: {}", path.display(), err);
+                Ok(Self { _file: None })
+            }
+        }
+    }
+}
+
+/// Reads the switcher size saved by --remember-size
+fn load_remembered_size() -> Option<Vec2> {
+    let content = fs::read_to_string(shellexpand::tilde(SIZE_CACHE_PATH).to_string()).ok()?;
+    let mut parts = content.split_whitespace().map(|part| part.parse::<f32>().ok());
+    let (width, height) = (parts.next()??, parts.next()??);
+    Some(Vec2::new(width, height))
+}
+
+/// Saves the user-chosen switcher size for the next launch
+fn save_remembered_size(size: Vec2) {
+    let path = shellexpand::tilde(SIZE_CACHE_PATH).to_string();
+    if let Some(parent) = Path::new(&path).parent() {
+        fs::create_dir_all(parent).ok();
+    }
+    if let Err(err) = fs::write(&path, format!("{} {}\n", size.x, size.y)) {
+        eprintln!("Warning: failed to save window size to {}: {}", path, err);
+    }
+}
+
+/// Runs a version probe, returning its stdout if the tool is installed and succeeded
+fn probe_tool(program: &str, args: &[&str]) -> Option<Vec<u8>> {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| output.stdout)
+}
+
+/// Prints version and environment details for bug reports, for --version-json
+fn print_version_json(config_path: &str) {
+    // hyprctl reports the release tag as JSON, nmcli prints "nmcli tool, version X"
+    let hyprctl = probe_tool("hyprctl", &["version", "-j"]);
+    let hyprctl_version = hyprctl.as_ref()
+        .and_then(|stdout| serde_json::from_slice::<serde_json::Value>(stdout).ok())
+        .and_then(|version| version["tag"].as_str().map(str::to_string));
+    let nmcli = probe_tool("nmcli", &["--version"]);
+    let nmcli_version = nmcli.as_ref()
+        .and_then(|stdout| String::from_utf8_lossy(stdout).split_whitespace().last().map(str::to_string));
+
+    let info = serde_json::json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "tools": {
+            "hyprctl": { "present": hyprctl.is_some(), "version": hyprctl_version },
+            "nmcli": { "present": nmcli.is_some(), "version": nmcli_version },
+        },
+        "paths": {
+            "colors": shellexpand::tilde(config_path).to_string(),
+            "config": shellexpand::tilde(CONFIG_PATH).to_string(),
+            "icon_overrides": shellexpand::tilde(ICON_OVERRIDES_PATH).to_string(),
+        },
+    });
+    println!("{}", serde_json::to_string_pretty(&info).unwrap_or_default());
+}
+
+/// Reads the first color of Hyprland's `general:col.active_border` gradient
+fn read_hypr_accent() -> Option<Color32> {
+    let output = hyprctl::run(["getoption", "general:col.active_border", "-j"])?;
+    let option: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+
+    // Gradients are printed as space-separated AARRGGBB values followed by an angle
+    let first = option["custom"].as_str()?.split_whitespace().next()?;
+    let argb = u32::from_str_radix(first.trim_start_matches("0x"), 16).ok()?;
+    let [a, r, g, b] = argb.to_be_bytes();
+    Some(Color32::from_rgba_unmultiplied(r, g, b, a))
 }
 
+/// Low battery and failed actions; not part of the palette, and readable on
+/// light and dark panels alike
+pub const WARNING_COLOR: Color32 = Color32::from_rgb(230, 90, 90);
+
 /// Color configuration for the application
 #[derive(Clone)]
 pub struct Colors {
@@ -139,8 +935,45 @@ pub struct Colors {
 }
 
 impl Colors {
-    fn new() -> Self {
-        read_colors_from_config().unwrap_or_else(|| Self {
+    /// Palette from the config (or the theme's defaults) with command line overrides applied on top
+    fn new(config_path: &str, theme: Theme, overrides: &[(&str, Color32)]) -> Self {
+        let (mut colors, _) = read_colors_from_config(config_path, theme);
+        colors.apply_overrides(overrides);
+        colors
+    }
+
+    fn apply_overrides(&mut self, overrides: &[(&str, Color32)]) {
+        for (key, color) in overrides {
+            if let Some(field) = self.field_mut(key) {
+                *field = *color;
+            }
+        }
+    }
+
+    fn field_mut(&mut self, key: &str) -> Option<&mut Color32> {
+        match key {
+            "surface_container_low" => Some(&mut self.surface_container_low),
+            "surface_container_high" => Some(&mut self.surface_container_high),
+            "on_surface_variant" => Some(&mut self.on_surface_variant),
+            "on_primary_fixed" => Some(&mut self.on_primary_fixed),
+            "primary_fixed_dim" => Some(&mut self.primary_fixed_dim),
+            "surface" => Some(&mut self.surface),
+            "surface_container" => Some(&mut self.surface_container),
+            "outline" => Some(&mut self.outline),
+            _ => None,
+        }
+    }
+
+    /// Built-in palette used when no config is available
+    fn defaults(theme: Theme) -> Self {
+        match theme {
+            Theme::Dark => Self::dark(),
+            Theme::Light => Self::light(),
+        }
+    }
+
+    fn dark() -> Self {
+        Self {
             surface_container_low: Color32::from_rgba_unmultiplied(27, 27, 33, 255),
             surface_container_high: Color32::from_rgba_unmultiplied(41, 42, 47, 255),
             on_surface_variant: Color32::from_rgba_unmultiplied(198, 197, 208, 255),
@@ -149,266 +982,927 @@ impl Colors {
             surface: Color32::from_rgba_unmultiplied(18, 19, 24, 255),
             surface_container: Color32::from_rgba_unmultiplied(31, 31, 37, 255),
             outline: Color32::from_rgba_unmultiplied(144, 144, 154, 255),
-        })
+        }
+    }
+
+    fn light() -> Self {
+        Self {
+            surface_container_low: Color32::from_rgba_unmultiplied(245, 242, 250, 255),
+            surface_container_high: Color32::from_rgba_unmultiplied(233, 231, 239, 255),
+            on_surface_variant: Color32::from_rgba_unmultiplied(69, 70, 79, 255),
+            on_primary_fixed: Color32::from_rgba_unmultiplied(8, 22, 75, 255),
+            primary_fixed_dim: Color32::from_rgba_unmultiplied(76, 90, 146, 255),
+            surface: Color32::from_rgba_unmultiplied(251, 248, 255, 255),
+            surface_container: Color32::from_rgba_unmultiplied(239, 237, 244, 255),
+            outline: Color32::from_rgba_unmultiplied(118, 118, 128, 255),
+        }
+    }
+
+    /// Palette entries paired with their config key names
+    fn fields(&self) -> [(&'static str, Color32); 8] {
+        [
+            ("surface_container_low", self.surface_container_low),
+            ("surface_container_high", self.surface_container_high),
+            ("on_surface_variant", self.on_surface_variant),
+            ("on_primary_fixed", self.on_primary_fixed),
+            ("primary_fixed_dim", self.primary_fixed_dim),
+            ("surface", self.surface),
+            ("surface_container", self.surface_container),
+            ("outline", self.outline),
+        ]
+    }
+}
+
+/// Corner radius from --rounding, or `default` when the flag isn't given
+pub fn corner_radius(rounding: Option<f32>, default: u8) -> u8 {
+    rounding.map_or(default, |radius| radius.round().clamp(0.0, u8::MAX as f32) as u8)
+}
+
+/// Colors the sliders in `ui` from the palette: accent fill and handle on a container-toned rail
+pub fn style_slider(ui: &mut eframe::egui::Ui, colors: &Colors) {
+    let visuals = ui.visuals_mut();
+    visuals.selection.bg_fill = colors.primary_fixed_dim;
+    visuals.widgets.inactive.bg_fill = colors.surface_container;
+    visuals.widgets.inactive.fg_stroke.color = colors.primary_fixed_dim;
+    for state in [&mut visuals.widgets.hovered, &mut visuals.widgets.active] {
+        state.bg_fill = colors.primary_fixed_dim;
+        state.fg_stroke.color = colors.primary_fixed_dim;
+    }
+}
+
+/// Makes the font at `path` the primary proportional font, keeping the icon glyphs as fallback
+fn load_custom_font(fonts: &mut eframe::egui::FontDefinitions, path: &str) {
+    let path = shellexpand::tilde(path).to_string();
+    match fs::read(&path) {
+        // egui panics on data it can't parse, so check for a TrueType/OpenType header first
+        Ok(bytes) if !matches!(bytes.get(..4), Some(b"\x00\x01\x00\x00" | b"OTTO" | b"true" | b"ttcf")) => {
+            eprintln!("Warning: {} is not a TTF/OTF font; using the default font", path);
+        }
+        Ok(bytes) => {
+            fonts.font_data.insert(
+                "custom".to_string(),
+                std::sync::Arc::new(eframe::egui::FontData::from_owned(bytes)),
+            );
+            fonts.families
+                .entry(eframe::egui::FontFamily::Proportional)
+                .or_default()
+                .insert(0, "custom".to_string());
+        }
+        Err(err) => eprintln!("Warning: could not read font {}: {}; using the default font", path, err),
+    }
+}
+
+/// Size of a window holding `widget_size` stacked under widgets taking up `size`
+fn stacked(size: Vec2, widget_size: Vec2) -> Vec2 {
+    Vec2::new(size.x.max(widget_size.x), size.y + widget_size.y)
+}
+
+/// Requests a repaint, no sooner than the frame interval allows when capped
+pub fn request_repaint(ctx: &Context, frame_interval: Option<Duration>) {
+    match frame_interval {
+        Some(interval) => ctx.request_repaint_after(interval),
+        None => ctx.request_repaint(),
     }
 }
 
+/// Starts `command` without waiting for it; a background thread reaps it once it
+/// exits, so it isn't left behind as a zombie
+pub fn spawn_reaped(command: &mut Command) -> std::io::Result<()> {
+    let mut child = command.spawn()?;
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Shortest wait before waking up for the next widget poll
+const MIN_POLL_DELAY: Duration = Duration::from_millis(50);
+
+/// What the keys handled for every widget do
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AppKey {
+    Help,
+    Close,
+}
+
 /// Main application state
 struct HyprWidgets {
-    workspace_switcher: Option<WorkspaceSwitcher>,
-    network_widget: Option<NetworkWidget>,
+    /// Shown widgets, stacked top to bottom in this order
+    panels: Vec<Box<dyn Panel>>,
+    colors: Colors,
+    show_help: bool,
     position: Position,
-    padding_top: i32,
-    padding_bottom: i32,
-    padding_left: i32,
-    padding_right: i32,
+    monitor: Option<String>,
+    padding_top: Padding,
+    padding_bottom: Padding,
+    padding_left: Padding,
+    padding_right: Padding,
+    pin: bool,
+    scale: Option<f32>,
+    frame_interval: Option<Duration>,
+    remember_size: bool,
+    remembered_size: Option<Vec2>,
+    requested_size: Option<Vec2>,
+    observed_size: Option<Vec2>,
+    positioned_at: Option<Instant>,
+    /// When the fade-in started, once the window was placed or positioning gave up
+    fade_in_since: Option<Instant>,
+    /// Whether our window has been found, floated and moved into place
+    positioned: bool,
+    attempts: i32,
+    animate: bool,
+    close_on_unfocus: bool,
+    rounding: Option<f32>,
+    /// Focus state seen last frame, to catch the window losing focus
+    was_focused: bool,
+    /// When the fade-out started; the window closes once it completes
+    closing_since: Option<Instant>,
+    /// Set with --daemon, which hides the window instead of closing it
+    daemon: Option<Daemon>,
+    /// Set by the daemon's quit command, so the fade-out ends in closing instead of hiding
+    quitting: bool,
+}
+
+/// State kept by --daemon between shows
+struct Daemon {
+    socket: ControlSocket,
+    /// Options every shown widget is built with
+    args: Args,
+    /// Widgets currently on screen; empty while hidden
+    shown: Vec<Widget>,
+    /// Whether the window still has to be moved out of sight
+    hide_pending: bool,
 }
 
+/// Special workspace the daemon parks its window on while hidden
+const HIDDEN_WORKSPACE: &str = "special:hypowertools";
+
 impl HyprWidgets {
-    fn new(args: Args) -> Self {
-        let colors = Colors::new();
-        Self {
-            workspace_switcher: if args.workspaces {
-                Some(WorkspaceSwitcher::new(colors.clone()))
-            } else {
-                None
-            },
-            network_widget: if args.network {
-                Some(NetworkWidget::new(colors))
-            } else {
-                None
-            },
-            position: args.position,
-            padding_top: args.padding_top,
-            padding_bottom: args.padding_bottom,
-            padding_left: args.padding_left,
-            padding_right: args.padding_right,
+    fn new(args: Args, socket: Option<ControlSocket>) -> Self {
+        let mut widgets = Self {
+            panels: Vec::new(),
+            colors: resolve_colors(&args),
+            show_help: false,
+            position: args.position.clone(),
+            monitor: args.monitor.clone(),
+            padding_top: args.padding_top.unwrap_or(Padding::Pixels(args.preset.padding())),
+            padding_bottom: args.padding_bottom.unwrap_or(Padding::Pixels(args.preset.padding())),
+            padding_left: args.padding_left.unwrap_or(Padding::Pixels(args.preset.padding())),
+            padding_right: args.padding_right.unwrap_or(Padding::Pixels(args.preset.padding())),
+            pin: !args.no_pin,
+            scale: args.scale,
+            frame_interval: args.frame_interval(),
+            remember_size: args.remember_size,
+            remembered_size: if args.remember_size { load_remembered_size() } else { None },
+            requested_size: None,
+            observed_size: None,
+            positioned_at: None,
+            fade_in_since: None,
+            positioned: false,
+            attempts: 0,
+            animate: !args.no_animation && !args.reduce_motion,
+            close_on_unfocus: args.close_on_unfocus,
+            rounding: args.rounding,
+            was_focused: false,
+            closing_since: None,
+            daemon: socket.map(|socket| {
+                // Widgets given alongside --daemon are shown right away
+                let shown = requested_widgets(&args);
+                Daemon {
+                    socket,
+                    hide_pending: shown.is_empty(),
+                    shown,
+                    args: args.clone(),
+                }
+            }),
+            quitting: false,
+        };
+        widgets.build_widgets(&args, &requested_widgets(&args));
+        widgets
+    }
+}
+
+/// Widgets selected on the command line, as named over the control socket
+///
+/// The overview comes first since it takes over the switcher when both are given.
+fn requested_widgets(args: &Args) -> Vec<Widget> {
+    [
+        (args.overview, Widget::Overview),
+        (args.workspaces, Widget::Workspaces),
+        (args.network, Widget::Network),
+        (args.battery, Widget::Battery),
+        (args.volume, Widget::Volume),
+        (args.brightness, Widget::Brightness),
+        (args.bluetooth, Widget::Bluetooth),
+        (args.clock, Widget::Clock),
+        (args.media, Widget::Media),
+        (args.sysmon, Widget::SysMon),
+        (args.keyboard, Widget::Keyboard),
+        (args.power, Widget::Power),
+    ]
+    .into_iter()
+    .filter_map(|(requested, widget)| requested.then_some(widget))
+    .collect()
+}
+
+/// Our window in `hyprctl clients`, matched by PID
+fn find_own_window() -> Option<serde_json::Value> {
+    let output = hyprctl::run(["clients", "-j"])?;
+    let clients: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).ok()?;
+    clients.into_iter().find(|client| client["pid"].as_u64() == Some(u64::from(process::id())))
+}
+
+/// Parks our window on the hidden special workspace; false if it isn't mapped yet
+fn hide_window() -> bool {
+    let Some(window) = find_own_window() else {
+        return false;
+    };
+    let Some(address) = window["address"].as_str() else {
+        return false;
+    };
+    let address_arg = format!("address:{}", address);
+
+    // Pinned windows show on every workspace, so unpin before moving
+    if window["pinned"].as_bool().unwrap_or(false) {
+        hyprctl::run(["dispatch", "pin", &address_arg]);
+    }
+    hyprctl::run(["dispatch", "movetoworkspacesilent", &format!("{},{}", HIDDEN_WORKSPACE, address_arg)]);
+    true
+}
+
+/// Runs on the control socket's thread, since a window on the hidden special
+/// workspace gets no frames and so never sees the command otherwise
+fn wake_hidden_window(command: &ControlCommand) {
+    let Some(window) = find_own_window() else {
+        return;
+    };
+    if window["workspace"]["name"].as_str() != Some(HIDDEN_WORKSPACE) {
+        return;
+    }
+    match command {
+        // Put the window where it can render; positioning then moves it into place
+        ControlCommand::Show(_) | ControlCommand::Toggle(_) => {
+            let (Some(address), Some(monitor)) = (window["address"].as_str(), get_active_monitor()) else {
+                return;
+            };
+            let target = format!("{},address:{}", monitor.active_workspace_id(), address);
+            hyprctl::run(["dispatch", "movetoworkspacesilent", &target]);
+        }
+        // Nothing is shown, so there's nothing to fade out
+        ControlCommand::Quit => {
+            fs::remove_file(control::socket_path()).ok();
+            process::exit(0);
+        }
+        ControlCommand::Hide => {}
+    }
+}
+
+/// Palette for the widgets, with --use-hypr-accent applied
+fn resolve_colors(args: &Args) -> Colors {
+    let mut colors = Colors::new(&args.colors_config, args.theme, &args.color_overrides());
+    if args.use_hypr_accent {
+        match read_hypr_accent() {
+            Some(accent) => colors.primary_fixed_dim = accent,
+            None => eprintln!("Warning: could not read Hyprland's active border color; using the palette accent"),
         }
     }
+    colors
+}
+
+impl HyprWidgets {
+    /// Creates `widgets` with the options in `args`, replacing any shown before
+    fn build_widgets(&mut self, args: &Args, widgets: &[Widget]) {
+        let colors = &self.colors;
+        let shown = |widget| widgets.contains(&widget);
+        self.panels.clear();
+        if shown(Widget::Workspaces) || shown(Widget::Overview) {
+            self.panels.push(Box::new(WorkspaceSwitcher::new(colors.clone(), SwitcherOptions {
+                middle_click_command: args.middle_click_command.clone(),
+                remap_indices: args.remap_indices,
+                monitor_workspaces: args.monitor_workspaces,
+                monitor: args.monitor.clone(),
+                hide_empty: args.hide_empty,
+                max_icons: args.max_icons,
+                icon_margin: args.icon_margin,
+                label_margin: args.label_margin,
+                overview: shown(Widget::Overview),
+                select: args.select,
+                overview_command: args.overview_command.clone(),
+                overview_key: args.overview_key,
+                bg_dim: args.bg_dim,
+                bg_tint: args.bg_tint,
+                reduce_motion: args.reduce_motion,
+                background: !args.no_background,
+                mark_xwayland: args.mark_xwayland,
+                colors_config: args.colors_config.clone(),
+                refresh_interval: args.refresh_ms.map_or(workspace_switcher::DEFAULT_REFRESH, Duration::from_millis),
+                rounding: args.rounding,
+                frame_interval: args.frame_interval(),
+                close_policy: if args.keep_open {
+                    ClosePolicy::KeepOpen
+                } else if args.once {
+                    ClosePolicy::Once
+                } else {
+                    ClosePolicy::NumberKeys
+                },
+            })));
+        }
+        if shown(Widget::Network) {
+            self.panels.push(Box::new(NetworkWidget::new(colors.clone(), NetworkOptions {
+                signal_style: args.signal_style,
+                layout: args.network_layout,
+                notify: args.notify,
+                labeled_buttons: args.labeled_buttons,
+                nm_start_command: args.nm_start_command.clone(),
+                autowidth: args.network_autowidth,
+                throughput: args.network_throughput,
+                sort: args.network_sort,
+                min_signal: args.min_signal,
+                captive_check: args.captive_check,
+                refresh_interval: args.refresh_ms.map_or(network_widget::DEFAULT_REFRESH, Duration::from_millis),
+                rounding: args.rounding,
+            })));
+        }
+        if shown(Widget::Battery) {
+            self.panels.push(Box::new(BatteryWidget::new(colors.clone(), BatteryOptions {
+                refresh_interval: args.refresh_ms.map_or(battery_widget::DEFAULT_REFRESH, Duration::from_millis),
+            })));
+        }
+        if shown(Widget::Volume) {
+            self.panels.push(Box::new(VolumeWidget::new(colors.clone(), VolumeOptions {
+                refresh_interval: args.refresh_ms.map_or(volume_widget::DEFAULT_REFRESH, Duration::from_millis),
+            })));
+        }
+        if shown(Widget::Brightness) {
+            self.panels.push(Box::new(BrightnessWidget::new(colors.clone(), BrightnessOptions {
+                refresh_interval: args.refresh_ms.map_or(brightness_widget::DEFAULT_REFRESH, Duration::from_millis),
+                // The network list moves its selection with the same keys
+                arrow_keys: !shown(Widget::Network),
+            })));
+        }
+        if shown(Widget::Bluetooth) {
+            self.panels.push(Box::new(BluetoothWidget::new(colors.clone(), BluetoothOptions {
+                refresh_interval: args.refresh_ms.map_or(bluetooth_widget::DEFAULT_REFRESH, Duration::from_millis),
+                rounding: args.rounding,
+            })));
+        }
+        if shown(Widget::Clock) {
+            self.panels.push(Box::new(ClockWidget::new(colors.clone(), ClockOptions {
+                format: args.clock_format.clone(),
+            })));
+        }
+        if shown(Widget::Media) {
+            self.panels.push(Box::new(MediaWidget::new(colors.clone(), MediaOptions {
+                refresh_interval: args.refresh_ms.map_or(media_widget::DEFAULT_REFRESH, Duration::from_millis),
+            })));
+        }
+        if shown(Widget::SysMon) {
+            self.panels.push(Box::new(SysMonWidget::new(colors.clone(), SysMonOptions {
+                refresh_interval: args.refresh_ms.map_or(sysmon_widget::DEFAULT_REFRESH, Duration::from_millis),
+            })));
+        }
+        if shown(Widget::Keyboard) {
+            self.panels.push(Box::new(KeyboardWidget::new(colors.clone(), KeyboardOptions {
+                refresh_interval: args.refresh_ms.map_or(keyboard_widget::DEFAULT_REFRESH, Duration::from_millis),
+            })));
+        }
+        if shown(Widget::Power) {
+            self.panels.push(Box::new(PowerWidget::new(colors.clone(), PowerOptions {
+                lock_command: args.power_lock_cmd.clone(),
+                logout_command: args.power_logout_cmd.clone(),
+                suspend_command: args.power_suspend_cmd.clone(),
+                reboot_command: args.power_reboot_cmd.clone(),
+                shutdown_command: args.power_shutdown_cmd.clone(),
+                rounding: args.rounding,
+            })));
+        }
+    }
+
+    /// Size of the widgets stacked one above the other, measured by `size`;
+    /// None when nothing is shown
+    fn stacked_size(&self, size: impl Fn(&dyn Panel) -> Vec2) -> Option<Vec2> {
+        self.panels.iter()
+            .map(|panel| size(panel.as_ref()))
+            .reduce(|top, bottom| Vec2::new(top.x.max(bottom.x), top.y + STACK_SPACING + bottom.y))
+    }
+
+    /// Whether the window may be resized; the size the user picks is what --remember-size keeps
+    fn resizable(&self) -> bool {
+        self.panels.iter().any(|panel| panel.resizable())
+    }
+}
+
+impl HyprWidgets {
+    /// Draws a widget in its panel frame and returns the size it needs
+    fn show_panel(ui: &mut eframe::egui::Ui, panel: &mut dyn Panel, rounding: Option<f32>) -> Vec2 {
+        let frame = Frame::NONE
+            .fill(panel.colors().surface_container_low)
+            .corner_radius(CornerRadius::same(corner_radius(rounding, panel.corner_radius())))
+            .inner_margin(Margin::same(panel.margin() as i8));
+
+        frame.show(ui, |ui| {
+            panel.show(ui);
+        });
+        panel.size()
+    }
+
+    /// Spaces a widget from the ones drawn above it, if any
+    fn add_stack_gap(ui: &mut eframe::egui::Ui, size: &mut Vec2) {
+        if *size != Vec2::ZERO {
+            ui.add_space(STACK_SPACING);
+            size.y += STACK_SPACING;
+        }
+    }
+
+    /// Starts the fade-out, or closes right away with --no-animation
+    fn close(&mut self, ctx: &Context) {
+        if self.closing_since.is_some() {
+            return;
+        }
+        if !self.animate {
+            self.finish_close(ctx);
+        } else {
+            self.closing_since = Some(Instant::now());
+            request_repaint(ctx, self.frame_interval);
+        }
+    }
+
+    /// Closes the window, or with --daemon hides it until the next request
+    fn finish_close(&mut self, ctx: &Context) {
+        if self.daemon.is_some() && !self.quitting {
+            self.hide();
+            ctx.request_repaint();
+        } else {
+            ctx.send_viewport_cmd(ViewportCommand::Close);
+        }
+    }
+
+    /// Whether a daemon's window is parked out of sight
+    fn is_hidden(&self) -> bool {
+        self.daemon.as_ref().is_some_and(|daemon| daemon.shown.is_empty())
+    }
+
+    /// Applies the commands a daemon received since the last frame
+    fn handle_control_commands(&mut self, ctx: &Context) {
+        let Some(daemon) = &self.daemon else {
+            return;
+        };
+        let commands: Vec<ControlCommand> = std::iter::from_fn(|| daemon.socket.try_recv()).collect();
+        for command in commands {
+            info!("Daemon command: {}", command);
+            let shown = self.daemon.as_ref().map(|daemon| daemon.shown.clone()).unwrap_or_default();
+            match command {
+                ControlCommand::Toggle(widgets) if widgets == shown => self.hide(),
+                ControlCommand::Show(widgets) | ControlCommand::Toggle(widgets) => self.show_widgets(ctx, widgets),
+                ControlCommand::Hide => self.hide(),
+                ControlCommand::Quit => {
+                    self.quitting = true;
+                    self.close(ctx);
+                }
+            }
+        }
+
+        if let Some(daemon) = &mut self.daemon {
+            if daemon.hide_pending {
+                // On startup the window may not be mapped yet; keep trying
+                if hide_window() {
+                    daemon.hide_pending = false;
+                } else {
+                    ctx.request_repaint_after(Duration::from_millis(100));
+                }
+            }
+        }
+    }
+
+    /// Builds `widgets` afresh and positions the daemon's window again, as on startup
+    fn show_widgets(&mut self, ctx: &Context, widgets: Vec<Widget>) {
+        let Some(daemon) = &mut self.daemon else {
+            return;
+        };
+        let args = daemon.args.clone();
+        daemon.shown = widgets.clone();
+        daemon.hide_pending = false;
+
+        // Re-read so palette changes apply without restarting the daemon
+        self.colors = resolve_colors(&args);
+        self.build_widgets(&args, &widgets);
+
+        self.positioned = false;
+        self.attempts = 0;
+        self.positioned_at = None;
+        self.fade_in_since = None;
+        self.requested_size = None;
+        self.observed_size = None;
+        self.closing_since = None;
+        self.was_focused = false;
+        self.show_help = false;
+        ctx.clear_animations();
+        ctx.request_repaint();
+    }
+
+    /// Drops the widgets and sends the daemon's window out of sight
+    fn hide(&mut self) {
+        if let Some(daemon) = &mut self.daemon {
+            daemon.shown.clear();
+            daemon.hide_pending = true;
+        }
+        self.panels.clear();
+        self.closing_since = None;
+        self.show_help = false;
+    }
+
+    /// Keys handled for every widget, with their help text
+    fn key_table() -> Vec<Shortcut<AppKey>> {
+        vec![
+            Shortcut::new("?", "Toggle this help", vec![(Key::Questionmark, AppKey::Help)]),
+            Shortcut::new("Esc", "Close", vec![(Key::Escape, AppKey::Close)]),
+        ]
+    }
+
+    /// Shortcuts handled by the active widgets, for the help overlay
+    fn shortcuts(&self) -> Vec<(String, String)> {
+        let mut shortcuts: Vec<_> = self.panels.iter().flat_map(|panel| panel.shortcuts()).collect();
+        shortcuts.extend(Self::key_table().iter().map(Shortcut::help));
+        shortcuts
+    }
+
+    fn show_help_overlay(&self, ctx: &Context) {
+        let screen = ctx.screen_rect();
+        Area::new(Id::new("help-overlay"))
+            .order(Order::Foreground)
+            .fixed_pos(screen.min)
+            .show(ctx, |ui| {
+                Frame::NONE
+                    .fill(self.colors.surface.gamma_multiply(0.92))
+                    .corner_radius(CornerRadius::same(corner_radius(self.rounding, 8)))
+                    .inner_margin(Margin::same(8))
+                    .show(ui, |ui| {
+                        ui.set_min_size(screen.size() - Vec2::splat(16.0));
+                        ui.set_max_size(screen.size() - Vec2::splat(16.0));
+                        ScrollArea::vertical().show(ui, |ui| {
+                            Grid::new("help-shortcuts").num_columns(2).spacing([16.0, 4.0]).show(ui, |ui| {
+                                for (key, description) in self.shortcuts() {
+                                    ui.label(RichText::new(key).color(self.colors.primary_fixed_dim).strong());
+                                    ui.label(RichText::new(description).color(self.colors.on_surface_variant));
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                    });
+            });
+    }
 }
 
 impl eframe::App for HyprWidgets {
-    fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.handle_control_commands(ctx);
+
+        // Checked before the widgets run so Escape ends an edit instead of closing
+        let editing = self.panels.iter().any(|panel| panel.is_editing()) || ctx.wants_keyboard_input();
+
+        // Fade out before closing; the window stays up until the animation completes
+        let opacity = match self.closing_since {
+            Some(since) => {
+                let progress = since.elapsed().as_secs_f32() / FADE_OUT.as_secs_f32();
+                if progress >= 1.0 {
+                    self.finish_close(ctx);
+                }
+                request_repaint(ctx, self.frame_interval);
+                (1.0 - progress).max(0.0)
+            }
+            None => 1.0,
+        };
+
+        // A hidden daemon only waits for the next command
+        if self.is_hidden() {
+            return;
+        }
+
+        // Applied every frame since the native scale is only known once the window exists
+        if let Some(scale) = self.scale {
+            ctx.set_pixels_per_point(scale);
+        }
+
         // First time initialization and positioning
-        static mut POSITIONED: bool = false;
-        static mut ATTEMPTS: i32 = 0;
-        unsafe {
-            if !POSITIONED && ATTEMPTS < 5 {
-                ATTEMPTS += 1;
-                eprintln!("Positioning attempt {}", ATTEMPTS);
-
-                // First find our window
-                if let Ok(output) = Command::new("hyprctl")
-                    .args(&["clients", "-j"])
-                    .output() {
-                    if let Ok(output_str) = String::from_utf8(output.stdout) {
-                        if let Ok(clients) = serde_json::from_str::<Vec<serde_json::Value>>(&output_str) {
-                            // Find our window by class name
-                            if let Some(window) = clients.iter().find(|c| {
-                                c["class"].as_str().map_or(false, |class| class == APP_ID)
-                            }) {
-                                if let Some(address) = window["address"].as_str() {
-                                    eprintln!("Found our window at address: {}", address);
-
-                                    // Focus our window first
-                                    Command::new("hyprctl")
-                                        .args(&["dispatch", "focuswindow", APP_ID])
-                                        .output()
-                                        .ok();
-
-                                    // thread::sleep(Duration::from_millis(100));
-
-                                    // Calculate the actual window size needed based on content
-                                    let size = if let Some(ws) = self.workspace_switcher.as_mut() {
-                                        // Ensure workspace data is up to date
-                                        ws.update();
-                                        
-                                        // Calculate width based on workspace count
-                                        let count = ws.workspace_count();
-                                        
-                                        // Each workspace button is ~142px wide (80px height * 16/9 aspect ratio + spacing)
-                                        // Add padding (12px) and margin (10px spacing between items)
-                                        let button_width = 142.0;
-                                        let spacing = 10.0;
-                                        let padding = 12.0; // 6px on each side
-                                        
-                                        // Calculate total width including padding and spacing
-                                        let width = (count as f32 * button_width) + // Width of all buttons
-                                                  ((count.saturating_sub(1)) as f32 * spacing) + // Spacing between buttons
-                                                  padding; // Total padding (6px on each side)
-                                        
-                                        // Keep height fixed at 92px
-                                        (width, 92.0)
-                                    } else if let Some(nw) = self.network_widget.as_mut() {
-                                        // Update network data
-                                        nw.update();
-                                        
-                                        // Use the network widget's size
-                                        let size = nw.size();
-                                        (size.x, size.y)
-                                    } else {
-                                        (100.0, 50.0) // Fallback
-                                    };
-
-                                    // Calculate position based on the position enum
-                                    let (x, y) = match self.position {
-                                        Position::Center => (960 - (size.0 / 2.0) as i32, 540 - (size.1 / 2.0) as i32),
-                                        Position::Top => (960 - (size.0 / 2.0) as i32, self.padding_top),
-                                        Position::TopLeft => (self.padding_left, self.padding_top),
-                                        Position::TopRight => (1920 - size.0 as i32 - self.padding_right, self.padding_top),
-                                        Position::Bottom => (960 - (size.0 / 2.0) as i32, 1080 - size.1 as i32 - self.padding_bottom),
-                                        Position::BottomLeft => (self.padding_left, 1080 - size.1 as i32 - self.padding_bottom),
-                                        Position::BottomRight => (1920 - size.0 as i32 - self.padding_right, 1080 - size.1 as i32 - self.padding_bottom),
-                                    };
-
-                                    eprintln!("Moving window to position: x={}, y={}", x, y);
-
-                                    // Make window floating and pin it
-                                    Command::new("hyprctl")
-                                        .args(&["dispatch", "togglefloating", APP_ID])
-                                        .output()
-                                        .ok();
-
-                                    // thread::sleep(Duration::from_millis(50));
-
-                                    // Move window to position
-                                    let move_cmd = format!("hyprctl dispatch movewindowpixel \"exact {} {},address:{}\"", x, y, address);
-                                    eprintln!("Running command: {}", move_cmd);
-                                    Command::new("sh")
-                                        .args(&["-c", &move_cmd])
-                                        .output()
-                                        .ok();
-
-                                    let resize_cmd = format!("hyprctl dispatch resizewindowpixel \"exact {} {},address:{}\"", size.0, size.1, address);
-                                    eprintln!("Running command: {}", resize_cmd);
-                                    Command::new("sh")
-                                        .args(&["-c", &resize_cmd])
-                                        .output()
-                                        .ok();
-                                    // thread::sleep(Duration::from_millis(50));
-
-                                    let address_arg = format!("address:{}", address);
-
-                                    Command::new("hyprctl")
-                                    .args(&["dispatch", "pin", &address_arg])
-                                    .output()
-                                    .ok();
-                                
-                         
-
-
-                                    POSITIONED = true;
-                                }
-                            }
+        if !self.positioned && self.attempts < MAX_POSITION_ATTEMPTS {
+            self.attempts += 1;
+            debug!("Positioning attempt {}", self.attempts);
+
+            // First find our window; by PID, since other instances share our class
+            if let Some(window) = find_own_window() {
+                if let Some(address) = window["address"].as_str() {
+                    debug!("Found our window at address: {}", address);
+
+                    // Focus our window first
+                    hyprctl::run(["dispatch", "focuswindow", &format!("address:{}", address)]);
+
+                    // thread::sleep(Duration::from_millis(100));
+
+                    // Stacked widgets share the window, one above the other
+                    let size = match (self.remembered_size.filter(|_| self.resizable()), self.stacked_size(|panel| panel.size())) {
+                        (Some(size), _) => (size.x, size.y),
+                        (None, Some(size)) => (size.x, size.y),
+                        (None, None) => (100.0, 50.0), // Fallback
+                    };
+
+                    // Sizes above are in egui points; Hyprland wants layout
+                    // pixels, which differ when --scale overrides the zoom
+                    let zoom = ctx.zoom_factor();
+                    let size = (size.0 * zoom, size.1 * zoom);
+
+                    // Position on the requested monitor, or the focused one
+                    let monitor = match &self.monitor {
+                        Some(spec) => find_monitor(spec).or_else(|| {
+                            warn!("Monitor {} not found, using the focused monitor", spec);
+                            get_active_monitor()
+                        }),
+                        None => get_active_monitor(),
+                    };
+                    let active_workspace = monitor.as_ref().map(|monitor| monitor.active_workspace_id());
+                    let (mon_x, mon_y, mon_w, mon_h) = match monitor {
+                        Some(monitor) => {
+                            let (width, height) = monitor.logical_size();
+                            info!("Positioning on monitor {}", monitor.name);
+                            (monitor.x, monitor.y, width, height)
+                        }
+                        None => {
+                            warn!("No monitor information available, assuming 1920x1080");
+                            (0, 0, 1920, 1080)
                         }
+                    };
+
+                    // Percentage paddings follow the monitor's height (top/bottom) or width (left/right)
+                    let padding_top = self.padding_top.resolve(mon_h);
+                    let padding_bottom = self.padding_bottom.resolve(mon_h);
+                    let padding_left = self.padding_left.resolve(mon_w);
+                    let padding_right = self.padding_right.resolve(mon_w);
+
+                    // Calculate position based on the position enum
+                    let (x, y) = match self.position {
+                        Position::Center => (mon_w / 2 - (size.0 / 2.0) as i32, mon_h / 2 - (size.1 / 2.0) as i32),
+                        Position::Top => (mon_w / 2 - (size.0 / 2.0) as i32, padding_top),
+                        Position::TopLeft => (padding_left, padding_top),
+                        Position::TopRight => (mon_w - size.0 as i32 - padding_right, padding_top),
+                        Position::Bottom => (mon_w / 2 - (size.0 / 2.0) as i32, mon_h - size.1 as i32 - padding_bottom),
+                        Position::BottomLeft => (padding_left, mon_h - size.1 as i32 - padding_bottom),
+                        Position::BottomRight => (mon_w - size.0 as i32 - padding_right, mon_h - size.1 as i32 - padding_bottom),
+                        Position::Exact(x, y) => (x, y),
+                    };
+                    // Keep the widget from spilling onto a neighbouring output
+                    let x = x.min(mon_w - size.0 as i32).max(0);
+                    let y = y.min(mon_h - size.1 as i32).max(0);
+                    let (x, y) = (mon_x + x, mon_y + y);
+
+                    info!("Moving window to position: x={}, y={}", x, y);
+
+                    // Bring a daemon's window onto the target monitor's workspace; the socket
+                    // thread may have revealed it on another monitor
+                    let elsewhere = window["workspace"]["id"].as_i64() != active_workspace.map(i64::from);
+                    if let (true, Some(workspace)) = (elsewhere, active_workspace) {
+                        hyprctl::run(["dispatch", "movetoworkspacesilent", &format!("{},address:{}", workspace, address)]);
                     }
-                }
 
-                if !POSITIONED {
-                    // Request a repaint to try again
-                    ctx.request_repaint();
+                    // Make window floating; a window shown again by the daemon already is
+                    if !window["floating"].as_bool().unwrap_or(false) {
+                        hyprctl::run(["dispatch", "togglefloating", &format!("address:{}", address)]);
+                    }
+
+                    // thread::sleep(Duration::from_millis(50));
+
+                    // Move window to position
+                    let move_arg = format!("exact {} {},address:{}", x, y, address);
+                    debug!("Running command: hyprctl dispatch movewindowpixel \"{}\"", move_arg);
+                    hyprctl::run(["dispatch", "movewindowpixel", &move_arg]);
+
+                    let resize_arg = format!("exact {} {},address:{}", size.0, size.1, address);
+                    debug!("Running command: hyprctl dispatch resizewindowpixel \"{}\"", resize_arg);
+                    hyprctl::run(["dispatch", "resizewindowpixel", &resize_arg]);
+                    // thread::sleep(Duration::from_millis(50));
+
+                    // Pin so the widget follows across workspaces, unless --no-pin
+                    if self.pin && !window["pinned"].as_bool().unwrap_or(false) {
+                        let address_arg = format!("address:{}", address);
+
+                        hyprctl::run(["dispatch", "pin", &address_arg]);
+                    }
+
+                    // Measured in points, like the viewport size checked below
+                    self.requested_size = Some(Vec2::new(size.0 / zoom, size.1 / zoom));
+                    self.positioned_at = Some(Instant::now());
+                    self.positioned = true;
                 }
             }
+
+            if !self.positioned {
+                // Request a repaint to try again
+                request_repaint(ctx, self.frame_interval);
+            }
         }
 
-        if let Some(switcher) = &mut self.workspace_switcher {
-            if switcher.should_update() {
-                switcher.update();
-                ctx.request_repaint();
+        // Fade in once the window sits where it belongs (or positioning has given up)
+        let fade_in = if self.animate {
+            if self.fade_in_since.is_none() && (self.positioned || self.attempts >= MAX_POSITION_ATTEMPTS) {
+                self.fade_in_since = Some(Instant::now());
+            }
+            let progress = self.fade_in_since.map_or(0.0, |since| since.elapsed().as_secs_f32() / FADE_IN.as_secs_f32());
+            if progress < 1.0 && self.fade_in_since.is_some() {
+                request_repaint(ctx, self.frame_interval);
             }
+            progress.min(1.0)
+        } else {
+            1.0
+        };
 
-            let mut size = Vec2::new(400.0, 92.0);
-            CentralPanel::default()
-                .frame(Frame::none())
-                .show(ctx, |ui| {
-                    ui.set_min_size(Vec2::new(0.0, 92.0));
-                    
-                    let frame = Frame::none()
-                        .fill(switcher.colors().surface_container_low)
-                        .rounding(Rounding::same(15))
-                        .inner_margin(Margin::same(6));
-
-                    frame.show(ui, |ui| {
-                        ui.spacing_mut().button_padding = Vec2::ZERO;
-                        ui.spacing_mut().item_spacing = Vec2::new(10.0, 0.0);
-                        
-                        switcher.show(ui);
-                        
-                        let rect = ui.min_rect();
-                        size = Vec2::new(rect.width() + 12.0, 92.0);
-                    });
-                });
-            
-            ctx.send_viewport_cmd(ViewportCommand::InnerSize(size));
+        for panel in &mut self.panels {
+            if panel.should_update() {
+                panel.update();
+                request_repaint(ctx, self.frame_interval);
+            }
         }
+        // egui only draws a frame on input or when asked, so wake up when the next poll is due
+        let next_update = self.panels.iter().filter_map(|panel| panel.next_update()).min();
+        if let Some(delay) = next_update {
+            // A floor keeps a refresh that outlasts its interval from spinning the loop
+            ctx.request_repaint_after(delay.max(MIN_POLL_DELAY));
+        }
+
+        // Widgets are stacked top to bottom in one window sized to fit them all
+        let mut size = Vec2::ZERO;
+        let panels = &mut self.panels;
+        let rounding = self.rounding;
+        CentralPanel::default()
+            .frame(Frame::NONE)
+            .show(ctx, |ui| {
+                ui.set_opacity(opacity * fade_in);
+                ui.spacing_mut().item_spacing.y = 0.0;
+
+                for panel in panels {
+                    Self::add_stack_gap(ui, &mut size);
+                    size = stacked(size, Self::show_panel(ui, panel.as_mut(), rounding));
+                }
+            });
 
-        if let Some(network) = &mut self.network_widget {
-            if network.should_update() {
-                network.update();
-                ctx.request_repaint();
+        // With --remember-size, a size change we didn't ask for came from the user
+        let settled = self.positioned_at.is_some_and(|at| at.elapsed() > RESIZE_GRACE);
+        if self.remember_size && settled && self.resizable() {
+            if let Some(current) = ctx.input(|i| i.viewport().inner_rect.map(|rect| rect.size())) {
+                let changed = self.observed_size.is_some_and(|observed| (observed - current).length() > 1.0);
+                let requested = self.requested_size.is_some_and(|requested| (requested - current).length() <= 1.0);
+                if changed && !requested {
+                    info!("Remembering window size {}x{}", current.x, current.y);
+                    self.remembered_size = Some(current);
+                    save_remembered_size(current);
+                }
+                self.observed_size = Some(current);
             }
+        }
 
-            let mut size = Vec2::new(132.0, 52.0);
-            CentralPanel::default()
-                .frame(Frame::none())
-                .show(ctx, |ui| {
-                    let frame = Frame::none()
-                        .fill(network.colors().surface_container_low)
-                        .rounding(Rounding::same(8))
-                        .inner_margin(Margin::same(6));
-
-                    frame.show(ui, |ui| {
-                        network.show(ui);
-                        
-                        // Get the actual size needed for the content
-                        let rect = ui.min_rect();
-                        size = Vec2::new(rect.width() + 12.0, 52.0);
-                    });
-                });
-            
+        // A remembered size wins over sizing to the content
+        if self.remembered_size.is_none() {
             ctx.send_viewport_cmd(ViewportCommand::InnerSize(size));
+            self.requested_size = Some(size);
         }
 
-        if ctx.input(|i| i.key_pressed(Key::Escape)) {
-            ctx.send_viewport_cmd(ViewportCommand::Close);
+        let table = Self::key_table();
+        let pressed = if editing { Vec::new() } else { ctx.input(|i| shortcut::pressed(i, &table)) };
+        if pressed.contains(&AppKey::Help) {
+            self.show_help = !self.show_help;
+        }
+        if self.show_help {
+            self.show_help_overlay(ctx);
+        }
+
+        let done = self.panels.iter().any(|panel| panel.close_requested());
+        if done || pressed.contains(&AppKey::Close) {
+            self.close(ctx);
+        }
+
+        // Only a focused-to-unfocused transition counts, so a window that
+        // never received focus isn't closed right away
+        let focused = ctx.input(|i| i.viewport().focused).unwrap_or(self.was_focused);
+        if self.close_on_unfocus && self.was_focused && !focused {
+            self.close(ctx);
         }
+        self.was_focused = focused;
     }
 }
 
 fn main() -> eframe::Result<()> {
-    let args = Args::parse();
+    let mut argv: Vec<OsString> = env::args_os().collect();
+    let file_args = config_file_args();
+    argv.splice(1..1, file_args);
+    let args = Args::parse_from(argv);
+
+    // Only our own warnings unless asked for more with --verbose or RUST_LOG
+    let mut logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("hypowertools=warn"));
+    if args.verbose {
+        logger.filter_module(module_path!(), log::LevelFilter::Debug);
+    }
+    logger.init();
+
+    if args.check_colors {
+        check_colors(&args.colors_config, args.theme, &args.color_overrides());
+        return Ok(());
+    }
+
+    if let Some(path) = &args.export_colors {
+        if let Err(err) = export_colors(path, &args.colors_config, args.theme, &args.color_overrides()) {
+            eprintln!("Failed to export colors to {}: {}", path, err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if args.version_json {
+        print_version_json(&args.colors_config);
+        return Ok(());
+    }
     
-    if !args.workspaces && !args.network {
-        eprintln!("No widget specified. Use --workspaces for workspace switcher or --network for network widget.");
+    // Hand the request to a running daemon instead of opening another window
+    if !args.daemon {
+        let widgets = requested_widgets(&args);
+        let command = args.send.clone().or_else(|| (!widgets.is_empty()).then_some(ControlCommand::Toggle(widgets)));
+        if let Some(command) = command {
+            match control::connect() {
+                Some(stream) => {
+                    if let Err(err) = control::send(stream, &command) {
+                        eprintln!("Daemon could not run '{}': {}", command, err);
+                        std::process::exit(1);
+                    }
+                    return Ok(());
+                }
+                None if args.send.is_some() => {
+                    eprintln!("No daemon is listening on {}", control::socket_path().display());
+                    std::process::exit(1);
+                }
+                None => {}
+            }
+        }
+    }
+
+    if !args.daemon && requested_widgets(&args).is_empty() {
+        eprintln!("No widget specified. Use --workspaces for workspace switcher, --overview for monitor overview, --network for network widget, --battery for battery widget, --volume for volume widget, --brightness for brightness widget, --bluetooth for Bluetooth widget, --clock for clock widget, --media for media widget, --sysmon for system monitor widget, --keyboard for keyboard layout widget or --power for power menu.");
+        std::process::exit(1);
+    }
+
+    // Every widget is placed through hyprctl, so a window would just sit there blank
+    if !hyprctl::is_available() {
+        eprintln!("Could not run hyprctl. hypowertools needs Hyprland, with hyprctl on PATH.");
         std::process::exit(1);
     }
 
-    // Set initial size based on widget type
-    let initial_size = if args.workspaces {
-        // Start with a reasonable default for one workspace, including padding
-        [154.0, 92.0] // 142px (button) + 12px (padding)
+    // Held until the window closes; dropping it releases the lock
+    let _lock = if args.single_instance {
+        // Keyed on the whole set, so --network doesn't stand in for --network --battery
+        let widget = requested_widgets(&args).iter().map(|widget| widget.name()).collect::<Vec<_>>().join("+");
+        match InstanceLock::acquire(APP_ID, &widget) {
+            Ok(lock) => Some(lock),
+            Err(Some(pid)) => {
+                eprintln!("Another {} instance is running (pid {}), focusing it", widget, pid);
+                hyprctl::run(["dispatch", "focuswindow", &format!("pid:{}", pid)]);
+                return Ok(());
+            }
+            Err(None) => {
+                eprintln!("Another {} instance is starting up", widget);
+                return Ok(());
+            }
+        }
     } else {
-        [400.0, 434.0] // Keep the network widget's original height
+        None
+    };
+
+    let socket = if args.daemon {
+        match ControlSocket::bind() {
+            Ok(socket) => Some(socket),
+            Err(err) => {
+                eprintln!("Failed to listen on {}: {}", control::socket_path().display(), err);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
+    // Widgets are created up front so the window can start at the size they need
+    let app = HyprWidgets::new(args.clone(), socket);
+    let initial_size = app.remembered_size.filter(|_| app.resizable())
+        .or_else(|| app.stacked_size(|panel| panel.size()))
+        // Only a daemon starts with nothing to show
+        .unwrap_or(Vec2::new(100.0, 50.0));
+    let viewport = ViewportBuilder::default()
+        .with_decorations(false)
+        .with_transparent(true)
+        .with_always_on_top()
+        .with_app_id(APP_ID.to_string())
+        .with_inner_size(initial_size)
+        // Only the workspace switcher, or a daemon that may show it, can be resized
+        .with_resizable(app.resizable() || args.daemon);
+    // A daemon's window has to fit whichever widgets it is asked to show, so it isn't capped
+    let viewport = match (args.daemon, app.stacked_size(|panel| panel.min_size()), app.stacked_size(|panel| panel.max_size())) {
+        (false, Some(min_size), Some(max_size)) => viewport.with_min_inner_size(min_size).with_max_inner_size(max_size),
+        _ => viewport.with_min_inner_size([100.0, 40.0]),
     };
 
     let options = eframe::NativeOptions {
-        viewport: ViewportBuilder::default()
-            .with_decorations(false)
-            .with_transparent(true)
-            .with_always_on_top()
-            .with_app_id(APP_ID.to_string())
-            .with_inner_size(initial_size)
-            .with_min_inner_size(if args.workspaces {
-                [154.0, 92.0] // Minimum size for workspace switcher
-            } else {
-                [400.0, 434.0] // Fixed size for network widget
-            })
-            .with_max_inner_size(if args.workspaces {
-                [1024.0, 92.0] // Maximum size for workspace switcher
-            } else {
-                [400.0, 434.0] // Fixed size for network widget
-            })
-            .with_resizable(args.workspaces), // Only allow resizing for workspace switcher
+        viewport,
         renderer: eframe::Renderer::Glow,
         ..Default::default()
     };
@@ -417,14 +1911,114 @@ fn main() -> eframe::Result<()> {
         APP_ID,
         options,
         Box::new(|cc| {
-            cc.egui_ctx.set_visuals(eframe::egui::Visuals::dark());
+            cc.egui_ctx.set_visuals(args.theme.visuals());
             
             // Initialize Phosphor icons
             let mut fonts = eframe::egui::FontDefinitions::default();
             egui_phosphor::add_to_fonts(&mut fonts, egui_phosphor::Variant::Regular);
+            if let Some(path) = &args.font {
+                load_custom_font(&mut fonts, path);
+            }
             cc.egui_ctx.set_fonts(fonts);
             
-            Ok(Box::new(HyprWidgets::new(args)))
+            let mut app = app;
+            if let Some(daemon) = &mut app.daemon {
+                daemon.socket.listen(cc.egui_ctx.clone(), wake_hidden_window);
+            }
+
+            Ok(Box::new(app))
         })
     )
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rgba_colors() {
+        assert_eq!(
+            parse_rgba_color("rgba(b9c3ff80)"),
+            Some(Color32::from_rgba_unmultiplied(0xb9, 0xc3, 0xff, 0x80))
+        );
+    }
+
+    #[test]
+    fn parses_opaque_rgb_colors() {
+        assert_eq!(parse_rgba_color("rgb(1b1b21)"), Some(Color32::from_rgb(0x1b, 0x1b, 0x21)));
+    }
+
+    #[test]
+    fn parses_hex_colors_with_and_without_alpha() {
+        assert_eq!(
+            parse_rgba_color("#b9c3ff"),
+            Some(Color32::from_rgba_unmultiplied(0xb9, 0xc3, 0xff, 0xff))
+        );
+        assert_eq!(
+            parse_rgba_color("#b9c3ff80"),
+            Some(Color32::from_rgba_unmultiplied(0xb9, 0xc3, 0xff, 0x80))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_colors() {
+        for input in ["", "#", "#b9c3f", "#b9c3ff8", "#gggggg", "#b9c3ffé", "rgba(b9c3ff)", "rgba(b9c3ff80", "rgb(b9c3ff80)", "rgb(b9c3ff", "b9c3ff", "rgba(aé12345)"] {
+            assert_eq!(parse_rgba_color(input), None, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn parses_exact_positions() {
+        assert!(matches!("120, -40".parse::<Position>(), Ok(Position::Exact(120, -40))));
+        assert!(matches!("Top-Left".parse::<Position>(), Ok(Position::TopLeft)));
+        for input in ["", "120", "120,", "a,b", "1.5,2", "left"] {
+            assert!(input.parse::<Position>().is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn parses_pixel_and_percent_paddings() {
+        assert!(matches!("48".parse::<Padding>(), Ok(Padding::Pixels(48))));
+        assert_eq!("5%".parse::<Padding>().unwrap().resolve(1080), 54);
+        assert_eq!(" 12.5 % ".parse::<Padding>().unwrap().resolve(1920), 240);
+        assert_eq!("100%".parse::<Padding>().unwrap().resolve(1440), 1440);
+        for input in ["", "%", "-1%", "101%", "ten", "5px"] {
+            assert!(input.parse::<Padding>().is_err(), "{:?}", input);
+        }
+    }
+
+    /// Parses `cli` with the flags of `config` placed in front, as `main` does
+    fn parse_with_config(config: &str, cli: &[&str]) -> Args {
+        let mut argv = vec![OsString::from("hypowertools")];
+        argv.extend(config_args(toml::from_str(config).unwrap(), "config.toml"));
+        argv.extend(cli.iter().map(OsString::from));
+        Args::try_parse_from(argv).unwrap()
+    }
+
+    #[test]
+    fn config_file_values_apply_when_not_on_the_command_line() {
+        let args = parse_with_config("position = \"top-left\"\nrounding = 4.0\nno-pin = true\nnotify = false", &["--clock"]);
+        assert!(matches!(args.position, Position::TopLeft));
+        assert_eq!(args.rounding, Some(4.0));
+        assert!(args.no_pin);
+        assert!(!args.notify);
+        assert!(args.clock);
+    }
+
+    #[test]
+    fn command_line_values_override_the_config_file() {
+        let args = parse_with_config("position = \"top\"\nrefresh_ms = 250\nonce = true", &["--position", "bottom", "--refresh-ms", "100", "--keep-open"]);
+        assert!(matches!(args.position, Position::Bottom));
+        assert_eq!(args.refresh_ms, Some(100));
+        assert!(args.keep_open);
+        assert!(!args.once);
+    }
+
+    #[test]
+    fn config_file_ignores_modes_and_widgets() {
+        let args = parse_with_config("daemon = true\nsingle-instance = true\nnetwork = true\ncheck_colors = true\nsend = \"quit\"\nbogus = 1", &["--clock"]);
+        assert!(!args.daemon && !args.single_instance && !args.network && !args.check_colors);
+        assert!(args.send.is_none());
+        assert!(args.clock);
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui::{
+    Align,
+    Button,
+    Color32,
+    Context,
+    Label,
+    Layout,
+    RichText,
+    Ui,
+    Vec2,
+};
+
+use super::panel::Panel;
+
+/// How often the player is read unless --refresh-ms says otherwise
+pub const DEFAULT_REFRESH: Duration = Duration::from_millis(1000);
+const MEDIA_MARGIN: f32 = 8.0;
+const MEDIA_WIDTH: f32 = 360.0;
+const MEDIA_HEIGHT: f32 = 64.0;
+/// Title and artist on separate lines, as `playerctl metadata --format` prints them
+const METADATA_FORMAT: &str = "{{title}}\n{{artist}}";
+
+/// Options for the media widget
+#[derive(Debug, Clone)]
+pub struct MediaOptions {
+    /// How often the player is read
+    pub refresh_interval: Duration,
+}
+
+/// What playerctl reports about the current player
+#[derive(Debug, Clone)]
+struct NowPlaying {
+    title: String,
+    artist: String,
+    playing: bool,
+}
+
+impl NowPlaying {
+    /// Reads the player playerctl picks; None when no player is running
+    fn read() -> Option<Self> {
+        let status = playerctl(&["status"])?;
+        let metadata = playerctl(&["metadata", "--format", METADATA_FORMAT]).unwrap_or_default();
+        let mut lines = metadata.lines();
+        Some(Self {
+            title: lines.next().unwrap_or_default().trim().to_string(),
+            artist: lines.next().unwrap_or_default().trim().to_string(),
+            playing: status.trim() == "Playing",
+        })
+    }
+}
+
+/// Runs playerctl and returns its output; it fails when there's no player
+fn playerctl(args: &[&str]) -> Option<String> {
+    let output = Command::new("playerctl").args(args).stderr(Stdio::null()).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Title, artist and playback buttons of the current media player
+pub struct MediaWidget {
+    colors: super::Colors,
+    options: MediaOptions,
+    now_playing: Option<NowPlaying>,
+    /// Buttons pressed since the last refresh; it runs them before reading the player,
+    /// so the new track shows without waiting for the next poll
+    commands: Vec<&'static str>,
+    refresh_sender: Sender<Option<NowPlaying>>,
+    refresh_results: Receiver<Option<NowPlaying>>,
+    /// Set by the refresh thread once its result is waiting in the channel
+    refresh_ready: Arc<AtomicBool>,
+    /// Context the refresh thread wakes; known once the widget has been shown
+    repaint_ctx: Arc<OnceLock<Context>>,
+    refreshing: bool,
+    /// False until the first refresh has come back
+    loaded: bool,
+    last_update: Instant,
+}
+
+impl MediaWidget {
+    pub fn new(colors: super::Colors, options: MediaOptions) -> Self {
+        let (refresh_sender, refresh_results) = mpsc::channel();
+        let mut widget = Self {
+            colors,
+            options,
+            now_playing: None,
+            commands: Vec::new(),
+            refresh_sender,
+            refresh_results,
+            refresh_ready: Arc::new(AtomicBool::new(false)),
+            repaint_ctx: Arc::new(OnceLock::new()),
+            refreshing: false,
+            loaded: false,
+            last_update: Instant::now(),
+        };
+        widget.start_refresh();
+        widget
+    }
+
+    /// Runs the pressed commands and reads the player on a background thread;
+    /// the result is picked up by `update`
+    fn start_refresh(&mut self) {
+        self.refreshing = true;
+        self.last_update = Instant::now();
+
+        let commands = std::mem::take(&mut self.commands);
+        let sender = self.refresh_sender.clone();
+        let ready = self.refresh_ready.clone();
+        let repaint_ctx = self.repaint_ctx.clone();
+        thread::spawn(move || {
+            // Waited for, so the read below sees the player after the command
+            for command in commands {
+                Command::new("playerctl").arg(command).stdout(Stdio::null()).stderr(Stdio::null()).status().ok();
+            }
+            if sender.send(NowPlaying::read()).is_ok() {
+                ready.store(true, Ordering::Release);
+                if let Some(ctx) = repaint_ctx.get() {
+                    ctx.request_repaint();
+                }
+            }
+        });
+    }
+
+    /// Queues play-pause, next or previous for the refresh thread
+    fn send(&mut self, command: &'static str) {
+        self.commands.push(command);
+    }
+
+    fn control_button(&self, ui: &mut Ui, icon: &str, hover: &str, size: f32) -> bool {
+        ui.add(
+            Button::new(RichText::new(icon).color(self.colors.primary_fixed_dim).size(size))
+            .fill(Color32::TRANSPARENT)
+            .frame(false)
+        ).on_hover_text(hover).clicked()
+    }
+}
+
+impl Panel for MediaWidget {
+    fn next_update(&self) -> Option<Duration> {
+        Some(self.options.refresh_interval.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        !self.commands.is_empty()
+            || self.last_update.elapsed() > self.options.refresh_interval
+            || self.refresh_ready.load(Ordering::Acquire)
+    }
+
+    /// Applies a finished refresh, and starts the next one when it's due
+    fn update(&mut self) {
+        self.refresh_ready.store(false, Ordering::Release);
+        while let Ok(now_playing) = self.refresh_results.try_recv() {
+            self.refreshing = false;
+            self.loaded = true;
+            // Read before the queued commands ran; the refresh running them will tell
+            if self.commands.is_empty() {
+                self.now_playing = now_playing;
+            }
+        }
+
+        let due = !self.commands.is_empty() || self.last_update.elapsed() > self.options.refresh_interval;
+        if !self.refreshing && due {
+            self.start_refresh();
+        }
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(MEDIA_WIDTH, MEDIA_HEIGHT)
+    }
+
+    fn margin(&self) -> f32 {
+        MEDIA_MARGIN
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        // Refreshes finishing later need a context to wake
+        self.repaint_ctx.get_or_init(|| ui.ctx().clone());
+
+        ui.horizontal(|ui| {
+            ui.set_width(MEDIA_WIDTH - 2.0 * MEDIA_MARGIN);
+            ui.set_height(MEDIA_HEIGHT - 2.0 * MEDIA_MARGIN);
+
+            let Some(now_playing) = self.now_playing.clone() else {
+                ui.label(RichText::new(egui_phosphor::regular::MUSIC_NOTES_SIMPLE).color(self.colors.outline).size(22.0));
+                if self.loaded {
+                    ui.label(RichText::new("Nothing playing").color(self.colors.outline).size(16.0));
+                }
+                return;
+            };
+
+            // Buttons are laid out first so long titles are truncated instead of pushing them off
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if self.control_button(ui, egui_phosphor::regular::SKIP_FORWARD, "Next", 20.0) {
+                    self.send("next");
+                }
+                let (icon, hover) = if now_playing.playing {
+                    (egui_phosphor::regular::PAUSE_CIRCLE, "Pause")
+                } else {
+                    (egui_phosphor::regular::PLAY_CIRCLE, "Play")
+                };
+                if self.control_button(ui, icon, hover, 30.0) {
+                    self.send("play-pause");
+                    if let Some(now_playing) = &mut self.now_playing {
+                        now_playing.playing = !now_playing.playing;
+                    }
+                }
+                if self.control_button(ui, egui_phosphor::regular::SKIP_BACK, "Previous", 20.0) {
+                    self.send("previous");
+                }
+
+                ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
+                    ui.label(RichText::new(egui_phosphor::regular::MUSIC_NOTES).color(self.colors.primary_fixed_dim).size(22.0));
+                    ui.vertical(|ui| {
+                        let title = if now_playing.title.is_empty() { "Unknown title" } else { &now_playing.title };
+                        ui.add(Label::new(RichText::new(title).color(self.colors.primary_fixed_dim).size(15.0)).truncate());
+                        if !now_playing.artist.is_empty() {
+                            ui.add(Label::new(RichText::new(&now_playing.artist).color(self.colors.on_surface_variant).size(13.0)).truncate());
+                        }
+                    });
+                });
+            });
+        });
+    }
+}
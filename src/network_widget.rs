@@ -1,22 +1,46 @@
 use std::{
-    process::Command,
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    path::Path,
+    process::{Command, Output, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+        OnceLock,
+    },
+    thread,
     time::{Duration, Instant},
 };
 
 use eframe::egui::{
+    Context,
     Color32,
     Frame,
     RichText,
-    Rounding,
     ScrollArea,
     Ui,
     Vec2,
     Layout,
     Align,
     Button,
-    ViewportCommand,
+    Key,
+    Label,
+    Sense,
+    CursorIcon,
+    Painter,
+    Rect,
+    FontId,
+    Shape,
+    Stroke,
+    TextEdit,
+    Spinner,
 };
 
+use super::panel::Panel;
+use super::shortcut::{self, Shortcut};
+
 // ENHANCEMENT: Add icons using egui_nerdfonts
 // To replace text with icons, add the following to Cargo.toml:
 //   egui_nerdfonts = "0.1.3"
@@ -33,44 +57,538 @@ use eframe::egui::{
 // - Forget: egui_nerdfonts::regular::NF_FA_TRASH
 // - Unknown networks: egui_nerdfonts::regular::NF_FA_QUESTION
 
+/// Height of the connection summary row above the network list
+const STATUS_HEADER_HEIGHT: f32 = 28.0;
+/// Height of the throughput line under the connection summary
+const THROUGHPUT_HEIGHT: f32 = 18.0;
+/// Height of the login prompt shown behind a captive portal
+const LOGIN_PROMPT_HEIGHT: f32 = 36.0;
+/// Height of the compact row layout, including the frame padding
+const ROW_LAYOUT_HEIGHT: f32 = 48.0;
+/// Number of networks shown in the compact row layout
+const ROW_LAYOUT_NETWORKS: usize = 4;
+/// SSIDs longer than this are cut off in the compact row layout
+const ROW_LAYOUT_SSID_CHARS: usize = 10;
+/// How often nmcli is polled unless --refresh-ms says otherwise
+pub const DEFAULT_REFRESH: Duration = Duration::from_millis(1000);
+/// How long a connection change must settle before it's notified
+const NOTIFY_DEBOUNCE: Duration = Duration::from_secs(3);
+/// How long a connection attempt may take before it's given up on
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// How long a first forget press waits for confirmation
+const FORGET_CONFIRM_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long the "Copied" confirmation replaces a copied label
+const COPY_FEEDBACK: Duration = Duration::from_millis(1200);
+
+/// Width of the list layout unless --network-autowidth sizes it to the SSIDs
+const LIST_WIDTH: f32 = 400.0;
+/// Bounds for the measured list width
+const AUTOWIDTH_MIN: f32 = 260.0;
+const AUTOWIDTH_MAX: f32 = 640.0;
+/// Row space around the SSID: paddings, star, band tag, signal and unknown indicators
+const ROW_CHROME_WIDTH: f32 = 136.0;
+/// Panel padding plus the scrollbar gutter
+const LIST_MARGIN: f32 = 16.0;
+
+/// Key that radio toggle failures are reported under
+const RADIO_ACTION: &str = "Wi-Fi radio";
+/// Key that rescan failures are reported under
+const RESCAN_ACTION: &str = "Wi-Fi rescan";
+/// Rescans requested closer together than this are ignored
+const RESCAN_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// Host answering 204 on plain HTTP unless a captive portal intercepts the request
+const CAPTIVE_CHECK_HOST: &str = "connectivitycheck.gstatic.com";
+const CAPTIVE_CHECK_PATH: &str = "/generate_204";
+/// Bounds resolving, connecting to and reading from the check host
+const CAPTIVE_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+/// How often the check repeats while a login is required
+const CAPTIVE_RECHECK: Duration = Duration::from_secs(5);
+
+/// SSIDs starred to stay near the top of the list
+const FAVORITES_PATH: &str = "~/.config/hypowertools/favorites.json";
+
+/// How the signal strength indicator is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignalStyle {
+    Glyph,
+    Bars,
+    Ring,
+}
+
+impl std::str::FromStr for SignalStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "glyph" => Ok(SignalStyle::Glyph),
+            "bars" => Ok(SignalStyle::Bars),
+            "ring" => Ok(SignalStyle::Ring),
+            _ => Err(format!("Invalid signal style: {}", s)),
+        }
+    }
+}
+
+/// Overall arrangement of the network widget
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NetworkLayout {
+    List,
+    Row,
+}
+
+impl std::str::FromStr for NetworkLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "list" => Ok(NetworkLayout::List),
+            "row" => Ok(NetworkLayout::Row),
+            _ => Err(format!("Invalid network layout: {}", s)),
+        }
+    }
+}
+
+/// Order of the networks below the connected one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NetworkSort {
+    Signal,
+    Name,
+}
+
+impl std::str::FromStr for NetworkSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "signal" => Ok(NetworkSort::Signal),
+            "name" => Ok(NetworkSort::Name),
+            _ => Err(format!("Invalid network sort: {}", s)),
+        }
+    }
+}
+
+/// Options for the network widget
+#[derive(Debug, Clone)]
+pub struct NetworkOptions {
+    pub signal_style: SignalStyle,
+    pub layout: NetworkLayout,
+    /// Send a desktop notification when the connection changes
+    pub notify: bool,
+    /// Show text labels next to the action button glyphs
+    pub labeled_buttons: bool,
+    /// Shell command offered to start NetworkManager when it isn't running
+    pub nm_start_command: String,
+    /// Fit the list width to the widest visible SSID
+    pub autowidth: bool,
+    /// How often the connection state is polled
+    pub refresh_interval: Duration,
+    /// Corner radius of the panel; controls are capped at their default radius
+    pub rounding: Option<f32>,
+    /// Show download and upload rates of the connected interface
+    pub throughput: bool,
+    pub sort: NetworkSort,
+    /// Available networks weaker than this are left out; known ones always show
+    pub min_signal: i32,
+    /// Probe for a captive portal after connecting; makes an HTTP request
+    pub captive_check: bool,
+}
+
 #[derive(Debug, Clone)]
 struct WifiNetwork {
     ssid: String,
     signal_strength: i32,
     security: String,
     is_known: bool,
+    /// Channel frequency in MHz; None for saved networks that aren't in range
+    frequency: Option<u32>,
+    /// MAC address of the access point
+    bssid: Option<String>,
+    channel: Option<u32>,
+}
+
+impl WifiNetwork {
+    /// Whether joining needs a password; nmcli lists open networks with no security
+    fn is_secured(&self) -> bool {
+        !matches!(self.security.trim(), "" | "--" | "none")
+    }
+
+    /// Short name of the band the access point transmits on
+    fn band_tag(&self) -> Option<&'static str> {
+        self.frequency.map(|frequency| match frequency {
+            5925.. => "6G",
+            4900.. => "5G",
+            _ => "2.4G",
+        })
+    }
+}
+
+/// Wi-Fi band restriction of a saved connection
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Band {
+    Auto,
+    TwoFourGhz,
+    FiveGhz,
+}
+
+impl Band {
+    const ALL: [Band; 3] = [Band::Auto, Band::TwoFourGhz, Band::FiveGhz];
+
+    fn from_nmcli(value: &str) -> Self {
+        match value.trim() {
+            "a" => Band::FiveGhz,
+            "bg" => Band::TwoFourGhz,
+            _ => Band::Auto,
+        }
+    }
+
+    /// Value for nmcli's `802-11-wireless.band` property; nmcli resets a
+    /// property to its default, here any band, when given an empty value
+    fn nmcli_value(self) -> &'static str {
+        match self {
+            Band::Auto => "",
+            Band::TwoFourGhz => "bg",
+            Band::FiveGhz => "a",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Band::Auto => "Auto",
+            Band::TwoFourGhz => "2.4 GHz",
+            Band::FiveGhz => "5 GHz",
+        }
+    }
+}
+
+/// Addressing details of the connected Wi-Fi device
+#[derive(Debug, Clone, Default)]
+struct ConnectionDetails {
+    /// Interface name, e.g. wlan0
+    device: Option<String>,
+    /// IPv4 addresses with their prefix length, e.g. 192.168.1.20/24
+    addresses: Vec<String>,
+    /// None until DHCP has handed out a lease
+    gateway: Option<String>,
+    dns: Vec<String>,
+}
+
+/// Outcome of an nmcli action run for a network
+struct ActionResult {
+    ssid: String,
+    /// nmcli's message when it exited with an error
+    error: Option<String>,
+}
+
+/// Connected wired device
+#[derive(Debug, Clone)]
+struct EthernetConnection {
+    device: String,
+    /// None until the device has been given an address
+    address: Option<String>,
+}
+
+/// VPN or WireGuard profile saved in NetworkManager
+#[derive(Debug, Clone)]
+struct VpnConnection {
+    name: String,
+    active: bool,
+}
+
+/// Byte counters of an interface at one point in time
+#[derive(Debug, Clone)]
+struct TrafficSample {
+    device: String,
+    rx_bytes: u64,
+    tx_bytes: u64,
+    at: Instant,
+}
+
+impl TrafficSample {
+    /// Reads the kernel's counters for `device`
+    fn read(device: &str) -> Option<Self> {
+        let counter = |name: &str| {
+            fs::read_to_string(format!("/sys/class/net/{}/statistics/{}", device, name))
+                .ok()
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+        Some(Self {
+            device: device.to_string(),
+            rx_bytes: counter("rx_bytes")?,
+            tx_bytes: counter("tx_bytes")?,
+            at: Instant::now(),
+        })
+    }
+
+    /// Download and upload rates in bytes per second since `previous`
+    fn rates_since(&self, previous: &TrafficSample) -> Option<(f64, f64)> {
+        let elapsed = self.at.duration_since(previous.at).as_secs_f64();
+        if previous.device != self.device || elapsed <= 0.0 {
+            return None;
+        }
+        // Counters reset when the interface goes down; treat that as no traffic
+        let rx = self.rx_bytes.saturating_sub(previous.rx_bytes) as f64 / elapsed;
+        let tx = self.tx_bytes.saturating_sub(previous.tx_bytes) as f64 / elapsed;
+        Some((rx, tx))
+    }
+}
+
+/// Formats a rate like `1.2 MB/s`
+fn format_rate(bytes_per_second: f64) -> String {
+    let mut value = bytes_per_second;
+    for unit in ["B/s", "KB/s", "MB/s"] {
+        if value < 1024.0 {
+            return format!("{:.1} {}", value, unit);
+        }
+        value /= 1024.0;
+    }
+    format!("{:.1} GB/s", value)
+}
+
+/// Splits a line of `nmcli -t` output into its fields; nmcli escapes colons and
+/// backslashes inside a field as `\:` and `\\`
+fn split_terse(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    fields.last_mut().unwrap().push(escaped);
+                }
+            }
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Runs nmcli to completion, writing `input` to its stdin if given
+fn run_nmcli(args: &[String], input: Option<&str>) -> std::io::Result<Output> {
+    let mut child = Command::new("nmcli")
+        .args(args)
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        // Dropping stdin afterwards closes it, so a second prompt fails instead of hanging
+        stdin.write_all(input.as_bytes())?;
+    }
+    child.wait_with_output()
+}
+
+/// Outcome of probing for a captive portal
+enum PortalCheck {
+    Online,
+    /// Page to open to log in
+    LoginRequired(String),
+}
+
+/// Requests a page that is empty with status 204 when nothing intercepts it; a captive
+/// portal answers with its login page or a redirect there instead
+///
+/// None when the check host couldn't be reached at all.
+fn check_captive_portal() -> Option<PortalCheck> {
+    let address = resolve_check_host()?;
+    let mut stream = TcpStream::connect_timeout(&address, CAPTIVE_CHECK_TIMEOUT).ok()?;
+    stream.set_read_timeout(Some(CAPTIVE_CHECK_TIMEOUT)).ok();
+    write!(
+        stream,
+        "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        CAPTIVE_CHECK_PATH, CAPTIVE_CHECK_HOST
+    ).ok()?;
+
+    let mut lines = BufReader::new(stream).lines().map_while(Result::ok);
+    let status = lines.next()?;
+    if status.split_whitespace().nth(1) == Some("204") {
+        return Some(PortalCheck::Online);
+    }
+    // Portals that don't redirect show their page in place of the check URL
+    let location = lines
+        .take_while(|line| !line.is_empty())
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("location").then(|| value.trim().to_string())
+        })
+        // The page is handed to xdg-open, which would open any scheme it knows
+        .filter(|location| is_web_url(location));
+    Some(PortalCheck::LoginRequired(
+        location.unwrap_or_else(|| format!("http://{}{}", CAPTIVE_CHECK_HOST, CAPTIVE_CHECK_PATH)),
+    ))
+}
+
+/// Looks up the check host; the system resolver has no timeout of its own, and
+/// captive networks are where DNS tends to hang
+fn resolve_check_host() -> Option<SocketAddr> {
+    let (sender, result) = mpsc::channel();
+    thread::spawn(move || {
+        let address = (CAPTIVE_CHECK_HOST, 80).to_socket_addrs().ok().and_then(|mut addresses| addresses.next());
+        sender.send(address).ok();
+    });
+    result.recv_timeout(CAPTIVE_CHECK_TIMEOUT).ok().flatten()
+}
+
+/// Whether `url` is a plain http or https address
+fn is_web_url(url: &str) -> bool {
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme.to_ascii_lowercase());
+    matches!(scheme.as_deref(), Some("http" | "https"))
 }
 
 #[derive(Debug, Clone)]
 enum ConnectionState {
     Disconnected,
+    /// SSID being joined and when the attempt started
+    Connecting(String, Instant),
     Connected(String),
 }
 
+/// What a background refresh needs to know to skip queries whose answers are current
+struct RefreshRequest {
+    connected: Option<String>,
+    wifi_enabled: bool,
+    /// List the networks even if the connection and radio are unchanged
+    refetch_networks: bool,
+    /// The connected device already has a lease
+    has_gateway: bool,
+    ethernet: Option<EthernetConnection>,
+    sort: NetworkSort,
+    captive_check: bool,
+    /// Probe for a captive portal even if the connection didn't change
+    recheck_portal: bool,
+}
+
+/// Everything one background refresh learned from nmcli
+struct RefreshResult {
+    networkmanager_running: bool,
+    wifi_enabled: bool,
+    current: Option<String>,
+    /// Known and available networks; None when they weren't listed again
+    networks: Option<(Vec<WifiNetwork>, Vec<WifiNetwork>)>,
+    /// Band of the new connection; None when the connection didn't change
+    band: Option<Band>,
+    /// None when the previous details are still current
+    details: Option<ConnectionDetails>,
+    ethernet: Option<EthernetConnection>,
+    vpn_connections: Vec<VpnConnection>,
+    /// None when no check ran or the check host was unreachable
+    portal: Option<PortalCheck>,
+}
+
+/// What the network list's keys do
+#[derive(Debug, Clone, Copy)]
+enum NetworkKey {
+    Up,
+    Down,
+    Expand,
+    Forget,
+}
+
 /// Main network widget
 pub struct NetworkWidget {
     colors: super::Colors,
+    options: NetworkOptions,
     connection_state: ConnectionState,
     known_networks: Vec<WifiNetwork>,
     available_networks: Vec<WifiNetwork>,
     last_update: Instant,
     expanded_network: Option<String>,
+    selected_index: Option<usize>,
+    pending_forget: Option<(String, Instant)>,
+    connected_band: Band,
+    connection_details: ConnectionDetails,
+    ethernet: Option<EthernetConnection>,
+    ethernet_expanded: bool,
+    vpn_connections: Vec<VpnConnection>,
+    last_rescan: Option<Instant>,
+    traffic_sample: Option<TrafficSample>,
+    /// Download and upload rates from the last two samples
+    throughput: Option<(f64, f64)>,
+    networkmanager_running: bool,
+    favorites: Vec<String>,
+    wifi_enabled: bool,
+    row_list_open: bool,
+    notify_pending: Option<Instant>,
+    notified_state: Option<String>,
+    copied: Option<(String, Instant)>,
+    /// Password typed for an unknown secured network, keyed by SSID
+    password_entry: Option<(String, String)>,
+    /// SSID and password typed into the open hidden network form
+    hidden_entry: Option<(String, String)>,
+    action_sender: Sender<ActionResult>,
+    action_results: Receiver<ActionResult>,
+    /// Last failed action, shown under its network until something succeeds
+    action_error: Option<(String, String)>,
+    refresh_sender: Sender<RefreshResult>,
+    refresh_results: Receiver<RefreshResult>,
+    /// Set by the refresh and action threads once a result is waiting in a channel
+    refresh_ready: Arc<AtomicBool>,
+    /// Context the refresh thread wakes; known once the widget has been shown
+    repaint_ctx: Arc<OnceLock<Context>>,
+    refreshing: bool,
+    /// An action finished, so the next refresh starts right away and lists the networks again
+    refetch_networks: bool,
+    /// False until the first refresh has come back
+    loaded: bool,
+    /// Login page of the captive portal holding back the connection
+    login_page: Option<String>,
+    /// Whether the current connection has been checked for a captive portal
+    portal_checked: bool,
+    last_portal_check: Option<Instant>,
     size: Vec2,
 }
 
 impl NetworkWidget {
-    pub fn new(colors: super::Colors) -> Self {
+    pub fn new(colors: super::Colors, options: NetworkOptions) -> Self {
+        let (action_sender, action_results) = mpsc::channel();
+        let (refresh_sender, refresh_results) = mpsc::channel();
         let mut widget = Self {
             colors,
+            options,
             connection_state: ConnectionState::Disconnected,
             known_networks: Vec::new(),
             available_networks: Vec::new(),
             last_update: Instant::now(),
             expanded_network: None,
+            selected_index: None,
+            pending_forget: None,
+            connected_band: Band::Auto,
+            connection_details: ConnectionDetails::default(),
+            ethernet: None,
+            ethernet_expanded: false,
+            vpn_connections: Vec::new(),
+            last_rescan: None,
+            traffic_sample: None,
+            throughput: None,
+            networkmanager_running: true,
+            favorites: Self::load_favorites(),
+            wifi_enabled: true,
+            row_list_open: false,
+            notify_pending: None,
+            notified_state: None,
+            copied: None,
+            password_entry: None,
+            hidden_entry: None,
+            action_sender,
+            action_results,
+            action_error: None,
+            refresh_sender,
+            refresh_results,
+            refresh_ready: Arc::new(AtomicBool::new(false)),
+            repaint_ctx: Arc::new(OnceLock::new()),
+            refreshing: false,
+            refetch_networks: false,
+            loaded: false,
+            login_page: None,
+            portal_checked: false,
+            last_portal_check: None,
             size: Vec2::new(400.0, 434.0), // Wider default size
         };
+        if widget.options.layout == NetworkLayout::Row {
+            widget.size = Vec2::new(400.0, ROW_LAYOUT_HEIGHT + 12.0);
+        }
         
-        widget.update();
+        widget.start_refresh();
         widget
     }
 
@@ -80,9 +598,9 @@ impl NetworkWidget {
             .output() {
             if let Ok(output) = String::from_utf8(output.stdout) {
                 for line in output.lines() {
-                    let parts: Vec<&str> = line.split(':').collect();
+                    let parts = split_terse(line);
                     if parts.len() >= 2 && parts[0] == "yes" {
-                        return Some(parts[1].to_string());
+                        return Some(parts[1].clone());
                     }
                 }
             }
@@ -90,7 +608,7 @@ impl NetworkWidget {
         None
     }
 
-    fn get_networks() -> (Vec<WifiNetwork>, Vec<WifiNetwork>) {
+    fn get_networks(sort: NetworkSort) -> (Vec<WifiNetwork>, Vec<WifiNetwork>) {
         let mut known = Vec::new();
         let mut available = Vec::new();
 
@@ -100,13 +618,16 @@ impl NetworkWidget {
             .output() {
             if let Ok(output) = String::from_utf8(output.stdout) {
                 for line in output.lines() {
-                    if let Some(name) = line.split(':').next() {
+                    if let Some(name) = split_terse(line).into_iter().next() {
                         if !name.contains("ethernet") && !name.contains("loopback") {
                             known.push(WifiNetwork {
-                                ssid: name.to_string(),
+                                ssid: name,
                                 signal_strength: 0,
                                 security: String::new(),
                                 is_known: true,
+                                frequency: None,
+                                bssid: None,
+                                channel: None,
                             });
                         }
                     }
@@ -116,15 +637,19 @@ impl NetworkWidget {
 
         // Get list of available networks
         if let Ok(output) = Command::new("nmcli")
-            .args(["-t", "-f", "SSID,SIGNAL,SECURITY,IN-USE", "device", "wifi", "list"])
+            .args(["-t", "-f", "SSID,SIGNAL,SECURITY,IN-USE,FREQ,BSSID,CHAN", "device", "wifi", "list"])
             .output() {
             if let Ok(output) = String::from_utf8(output.stdout) {
                 for line in output.lines() {
-                    let parts: Vec<&str> = line.split(':').collect();
-                    if parts.len() >= 4 {
-                        let ssid = parts[0].to_string();
+                    let parts = split_terse(line);
+                    if parts.len() >= 7 {
+                        let ssid = parts[0].clone();
                         let signal = parts[1].parse().unwrap_or(0);
-                        let security = parts[2].to_string();
+                        let security = parts[2].clone();
+                        // Reported as e.g. "5180 MHz"
+                        let frequency = parts[4].split_whitespace().next().and_then(|mhz| mhz.parse().ok());
+                        let bssid = Some(parts[5].clone()).filter(|bssid| !bssid.is_empty());
+                        let channel = parts[6].parse().ok();
                         
                         // Skip empty SSIDs
                         if ssid.is_empty() {
@@ -139,6 +664,9 @@ impl NetworkWidget {
                             signal_strength: signal,
                             security,
                             is_known,
+                            frequency,
+                            bssid,
+                            channel,
                         };
 
                         if is_known {
@@ -146,6 +674,9 @@ impl NetworkWidget {
                             if let Some(known_net) = known.iter_mut().find(|n| n.ssid == network.ssid) {
                                 known_net.signal_strength = network.signal_strength;
                                 known_net.security = network.security;
+                                known_net.frequency = network.frequency;
+                                known_net.bssid = network.bssid;
+                                known_net.channel = network.channel;
                             }
                         } else {
                             available.push(network);
@@ -155,44 +686,555 @@ impl NetworkWidget {
             }
         }
 
-        // Sort networks by signal strength
-        known.sort_by(|a, b| b.signal_strength.cmp(&a.signal_strength));
-        available.sort_by(|a, b| b.signal_strength.cmp(&a.signal_strength));
+        match sort {
+            // Sort networks by signal strength, breaking ties by name so equal
+            // signals don't swap places between refreshes
+            NetworkSort::Signal => {
+                let by_signal = |a: &WifiNetwork, b: &WifiNetwork| {
+                    b.signal_strength.cmp(&a.signal_strength).then_with(|| a.ssid.cmp(&b.ssid))
+                };
+                known.sort_by(by_signal);
+                available.sort_by(by_signal);
+            }
+            NetworkSort::Name => {
+                known.sort_by_cached_key(|network| network.ssid.to_lowercase());
+                available.sort_by_cached_key(|network| network.ssid.to_lowercase());
+            }
+        }
 
         (known, available)
     }
 
-    pub fn should_update(&self) -> bool {
-        self.last_update.elapsed() > Duration::from_millis(1000)
+    fn get_wifi_enabled() -> bool {
+        Command::new("nmcli")
+            .args(["radio", "wifi"])
+            .output()
+            .ok()
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .is_none_or(|state| state.trim() != "disabled")
+    }
+
+    /// Name of the connected device of `device_type` (wifi, ethernet)
+    fn get_connected_device(device_type: &str) -> Option<String> {
+        let output = Command::new("nmcli")
+            .args(["-t", "-f", "DEVICE,TYPE,STATE", "device"])
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8(output.stdout).ok()?;
+        stdout.lines()
+            .map(split_terse)
+            .find(|parts| parts.len() >= 3 && parts[1] == device_type && parts[2] == "connected")
+            .map(|parts| parts[0].clone())
+    }
+
+    /// First IPv4 address of `device`, with its prefix length
+    fn get_device_address(device: &str) -> Option<String> {
+        let output = Command::new("nmcli")
+            .args(["-t", "-f", "IP4.ADDRESS", "device", "show", device])
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8(output.stdout).ok()?;
+        stdout.lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(_, value)| value.trim())
+            .find(|value| !value.is_empty() && *value != "--")
+            .map(str::to_string)
+    }
+
+    /// Names of `connection show` entries that are VPN profiles
+    fn get_vpn_names(extra_args: &[&str]) -> Vec<String> {
+        let Some(output) = Command::new("nmcli")
+            .args(["-t", "-f", "NAME,TYPE", "connection", "show"])
+            .args(extra_args)
+            .output()
+            .ok()
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+        else {
+            return Vec::new();
+        };
+        output.lines()
+            .map(split_terse)
+            .filter(|parts| parts.len() >= 2 && matches!(parts[1].as_str(), "vpn" | "wireguard"))
+            .map(|parts| parts[0].clone())
+            .collect()
+    }
+
+    /// Saved VPN profiles, marked with whether they are up
+    fn get_vpn_connections() -> Vec<VpnConnection> {
+        let active = Self::get_vpn_names(&["--active"]);
+        Self::get_vpn_names(&[])
+            .into_iter()
+            .map(|name| VpnConnection { active: active.contains(&name), name })
+            .collect()
+    }
+
+    /// Wired connection to list above the Wi-Fi networks, keeping a known address
+    fn get_ethernet(previous: Option<&EthernetConnection>) -> Option<EthernetConnection> {
+        let device = Self::get_connected_device("ethernet")?;
+        let address = match previous {
+            Some(previous) if previous.device == device && previous.address.is_some() => previous.address.clone(),
+            _ => Self::get_device_address(&device),
+        };
+        Some(EthernetConnection { device, address })
     }
 
-    pub fn update(&mut self) {
+    /// Addresses, gateway and DNS servers of the connected Wi-Fi device
+    fn get_connection_details() -> ConnectionDetails {
+        let mut details = ConnectionDetails::default();
+        let Some(device) = Self::get_connected_device("wifi") else {
+            return details;
+        };
+        details.device = Some(device.clone());
+        let Some(output) = Command::new("nmcli")
+            .args(["-t", "-f", "IP4.ADDRESS,IP4.GATEWAY,IP4.DNS", "device", "show", &device])
+            .output()
+            .ok()
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+        else {
+            return details;
+        };
+
+        // Addresses and DNS servers are listed one per line as IP4.DNS[1], IP4.DNS[2], ...
+        for line in output.lines() {
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            if value.is_empty() || value == "--" {
+                continue;
+            }
+            if field.starts_with("IP4.ADDRESS") {
+                details.addresses.push(value.to_string());
+            } else if field == "IP4.GATEWAY" {
+                details.gateway = Some(value.to_string());
+            } else if field.starts_with("IP4.DNS") {
+                details.dns.push(value.to_string());
+            }
+        }
+        details
+    }
+
+    fn load_favorites() -> Vec<String> {
+        let path = shellexpand::tilde(FAVORITES_PATH).to_string();
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Vec::new();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|err| {
+            eprintln!("Warning: ignoring invalid {}: {}", path, err);
+            Vec::new()
+        })
+    }
+
+    /// Stars or unstars a network and saves the list
+    fn toggle_favorite(&mut self, ssid: &str) {
+        if let Some(index) = self.favorites.iter().position(|favorite| favorite == ssid) {
+            self.favorites.remove(index);
+        } else {
+            self.favorites.push(ssid.to_string());
+        }
+
+        let path = shellexpand::tilde(FAVORITES_PATH).to_string();
+        if let Some(parent) = Path::new(&path).parent() {
+            fs::create_dir_all(parent).ok();
+        }
+        let saved = serde_json::to_string_pretty(&self.favorites)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(&path, json).map_err(|err| err.to_string()));
+        if let Err(err) = saved {
+            eprintln!("Warning: failed to save favorites to {}: {}", path, err);
+        }
+    }
+
+    fn get_band(connection: &str) -> Band {
+        Command::new("nmcli")
+            .args(["-g", "802-11-wireless.band", "connection", "show", connection])
+            .output()
+            .ok()
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map_or(Band::Auto, |value| Band::from_nmcli(&value))
+    }
+
+    /// Restricts a saved connection to a band and reconnects so it takes effect
+    fn set_band(&mut self, connection: &str, band: Band) {
+        // Shown right away; the next refresh reads back what nmcli stored
+        self.connected_band = band;
+        self.run_actions(connection, &[
+            &["connection", "modify", connection, "802-11-wireless.band", band.nmcli_value()],
+            &["connection", "up", connection],
+        ], None);
+    }
+
+    /// Runs an nmcli action for `ssid` in the background; `update` picks up the outcome
+    fn run_action(&mut self, ssid: &str, args: &[&str]) {
+        self.run_actions(ssid, &[args], None);
+    }
+
+    /// Runs nmcli actions for `ssid` one after another in the background, stopping
+    /// at the first that fails; `update` picks up the outcome
+    ///
+    /// `input` is written to each command's stdin, which keeps secrets such as
+    /// passwords out of the process list.
+    fn run_actions(&mut self, ssid: &str, commands: &[&[&str]], input: Option<&str>) {
+        // A new attempt replaces the last complaint about this network
+        if self.action_error.as_ref().is_some_and(|(failed, _)| failed == ssid) {
+            self.action_error = None;
+        }
+
+        let ssid = ssid.to_string();
+        let commands: Vec<Vec<String>> = commands.iter()
+            .map(|args| args.iter().map(|arg| arg.to_string()).collect())
+            .collect();
+        let input = input.map(str::to_string);
+        let sender = self.action_sender.clone();
+        let ready = self.refresh_ready.clone();
+        let repaint_ctx = self.repaint_ctx.clone();
+        thread::spawn(move || {
+            let error = commands.iter().find_map(|args| match run_nmcli(args, input.as_deref()) {
+                Ok(output) if output.status.success() => None,
+                Ok(output) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let message = stderr.lines().map(str::trim).find(|line| !line.is_empty())
+                        .map(|line| line.strip_prefix("Error: ").unwrap_or(line).to_string())
+                        .unwrap_or_else(|| format!("nmcli failed ({})", output.status));
+                    Some(message)
+                }
+                Err(err) => Some(format!("Failed to run nmcli: {}", err)),
+            });
+            if sender.send(ActionResult { ssid, error }).is_ok() {
+                ready.store(true, Ordering::Release);
+                if let Some(ctx) = repaint_ctx.get() {
+                    ctx.request_repaint();
+                }
+            }
+        });
+    }
+
+    /// Runs an nmcli action that joins `ssid`, showing it as connecting until it's up
+    fn connect(&mut self, ssid: &str, args: &[&str], input: Option<&str>) {
+        self.connection_state = ConnectionState::Connecting(ssid.to_string(), Instant::now());
+        self.run_actions(ssid, &[args], input);
+    }
+
+    /// Joins a network that has no saved connection yet
+    ///
+    /// `hidden` networks don't broadcast their SSID, so nmcli has to probe for them.
+    fn connect_new(&mut self, ssid: &str, password: Option<&str>, hidden: bool) {
+        // The SSID is passed as its own argument, so spaces need no quoting. The
+        // password is answered to the --ask prompt instead, since arguments can be
+        // read by any user through /proc
+        let mut args = if password.is_some() { vec!["--ask"] } else { Vec::new() };
+        args.extend(["device", "wifi", "connect", ssid]);
+        if hidden {
+            args.extend(["hidden", "yes"]);
+        }
+        let input = password.map(|password| format!("{}\n", password));
+        self.connect(ssid, &args, input.as_deref());
+    }
+
+    /// Whether a rescan was started too recently to start another
+    fn is_rescan_debounced(&self) -> bool {
+        self.last_rescan.is_some_and(|at| at.elapsed() < RESCAN_DEBOUNCE)
+    }
+
+    /// Asks NetworkManager to scan again; the list is refetched once nmcli reports back
+    fn rescan(&mut self) {
+        if self.is_rescan_debounced() {
+            return;
+        }
+        self.last_rescan = Some(Instant::now());
+        self.run_action(RESCAN_ACTION, &["device", "wifi", "rescan"]);
+    }
+
+    /// Turns the Wi-Fi radio on or off, showing the new state right away
+    fn set_wifi_enabled(&mut self, enabled: bool) {
+        self.wifi_enabled = enabled;
+        self.run_action(RADIO_ACTION, &["radio", "wifi", if enabled { "on" } else { "off" }]);
+    }
+
+    /// Masked input shared by the unknown and hidden network forms
+    fn password_edit<'a>(colors: &super::Colors, password: &'a mut String) -> TextEdit<'a> {
+        TextEdit::singleline(password)
+            .password(true)
+            .hint_text("Password")
+            .text_color(colors.on_surface_variant)
+            .background_color(colors.surface_container)
+            .vertical_align(Align::Center)
+    }
+
+    /// Keys handled in `show`, with their help text
+    fn key_table() -> Vec<Shortcut<NetworkKey>> {
+        vec![
+            Shortcut::new("↑ / ↓", "Select a network", vec![(Key::ArrowUp, NetworkKey::Up), (Key::ArrowDown, NetworkKey::Down)]),
+            Shortcut::new("Enter", "Expand the selected network", vec![(Key::Enter, NetworkKey::Expand)]),
+            Shortcut::new("Delete", "Forget the selected network (press twice)", vec![(Key::Delete, NetworkKey::Forget)]),
+        ]
+    }
+
+    fn connection_message(&self) -> String {
+        match &self.connection_state {
+            ConnectionState::Connected(ssid) => format!("Connected to {}", ssid),
+            ConnectionState::Connecting(ssid, _) => format!("Connecting to {}", ssid),
+            ConnectionState::Disconnected => "Disconnected".to_string(),
+        }
+    }
+
+    /// Notifies once a connection change has settled, so flapping doesn't spam
+    fn notify_connection_change(&mut self, connection_changed: bool) {
+        // An attempt in progress hasn't settled yet
+        if matches!(self.connection_state, ConnectionState::Connecting(..)) {
+            return;
+        }
+        if connection_changed {
+            self.notify_pending = Some(Instant::now());
+        }
+        match self.notify_pending {
+            Some(changed_at) if changed_at.elapsed() >= NOTIFY_DEBOUNCE => self.notify_pending = None,
+            _ => return,
+        }
+
+        let message = self.connection_message();
+        if self.notified_state.as_ref() != Some(&message) {
+            super::spawn_reaped(Command::new("notify-send").args(["--app-name", "hypowertools", "Network", &message])).ok();
+            self.notified_state = Some(message);
+        }
+    }
+
+    /// False when nmcli reports that the NetworkManager daemon is down
+    fn get_networkmanager_running() -> bool {
+        match Command::new("nmcli").args(["-t", "-f", "RUNNING", "general"]).output() {
+            Ok(output) => output.status.success()
+                || !String::from_utf8_lossy(&output.stderr).contains("NetworkManager is not running"),
+            // Without nmcli there is nothing to start
+            Err(_) => true,
+        }
+    }
+
+    /// Records the outcome of finished nmcli actions; true if any finished
+    fn collect_action_results(&mut self) -> bool {
+        let mut finished = false;
+        while let Ok(result) = self.action_results.try_recv() {
+            match result.error {
+                Some(message) => {
+                    eprintln!("Warning: nmcli failed for {}: {}", result.ssid, message);
+                    // A failed attempt won't come up, so stop waiting for it
+                    if matches!(&self.connection_state, ConnectionState::Connecting(ssid, _) if *ssid == result.ssid) {
+                        self.connection_state = ConnectionState::Disconnected;
+                    }
+                    self.action_error = Some((result.ssid, message));
+                }
+                None => {
+                    if self.action_error.as_ref().is_some_and(|(failed, _)| *failed == result.ssid) {
+                        self.action_error = None;
+                    }
+                    // The hidden network form has done its job
+                    if self.hidden_entry.as_ref().is_some_and(|(ssid, _)| ssid.trim() == result.ssid) {
+                        self.hidden_entry = None;
+                    }
+                }
+            }
+            finished = true;
+        }
+        finished
+    }
+
+    /// Queries nmcli on a background thread; the result is picked up by `update`
+    fn start_refresh(&mut self) {
+        let request = RefreshRequest {
+            connected: match &self.connection_state {
+                ConnectionState::Connected(name) => Some(name.clone()),
+                ConnectionState::Connecting(..) | ConnectionState::Disconnected => None,
+            },
+            wifi_enabled: self.wifi_enabled,
+            refetch_networks: self.refetch_networks || self.known_networks.is_empty() && self.available_networks.is_empty(),
+            has_gateway: self.connection_details.gateway.is_some(),
+            ethernet: self.ethernet.clone(),
+            sort: self.options.sort,
+            captive_check: self.options.captive_check,
+            recheck_portal: self.options.captive_check
+                && (!self.portal_checked || self.login_page.is_some())
+                && self.last_portal_check.is_none_or(|at| at.elapsed() >= CAPTIVE_RECHECK),
+        };
+        if request.recheck_portal {
+            self.last_portal_check = Some(Instant::now());
+        }
+        self.refetch_networks = false;
+        self.refreshing = true;
+        self.last_update = Instant::now();
+
+        let sender = self.refresh_sender.clone();
+        let ready = self.refresh_ready.clone();
+        let repaint_ctx = self.repaint_ctx.clone();
+        thread::spawn(move || {
+            if sender.send(Self::refresh(request)).is_ok() {
+                ready.store(true, Ordering::Release);
+                if let Some(ctx) = repaint_ctx.get() {
+                    ctx.request_repaint();
+                }
+            }
+        });
+
+        if self.options.throughput {
+            self.sample_throughput();
+        }
+    }
+
+    /// Runs the nmcli queries of one refresh; blocks for as long as nmcli takes
+    fn refresh(request: RefreshRequest) -> RefreshResult {
+        if !Self::get_networkmanager_running() {
+            return RefreshResult {
+                networkmanager_running: false,
+                wifi_enabled: request.wifi_enabled,
+                current: None,
+                networks: None,
+                band: None,
+                details: None,
+                ethernet: None,
+                vpn_connections: Vec::new(),
+                portal: None,
+            };
+        }
+
+        let ethernet = Self::get_ethernet(request.ethernet.as_ref());
+        let vpn_connections = Self::get_vpn_connections();
+        let wifi_enabled = Self::get_wifi_enabled();
         let current = Self::get_current_network();
-        let connection_changed = match (&self.connection_state, &current) {
+        let connection_changed = current != request.connected;
+
+        // Only fetch all networks if connection changed, an action may have added or
+        // removed a saved one, or none are available
+        let networks = (connection_changed || wifi_enabled != request.wifi_enabled || request.refetch_networks)
+            .then(|| Self::get_networks(request.sort));
+        let band = connection_changed.then(|| current.as_deref().map_or(Band::Auto, Self::get_band));
+
+        // The lease can arrive a moment after the association, so keep asking until it does
+        let details = match current {
+            Some(_) if connection_changed || !request.has_gateway => Some(Self::get_connection_details()),
+            Some(_) => None,
+            None => Some(ConnectionDetails::default()),
+        };
+
+        let check_portal = request.recheck_portal || connection_changed && request.captive_check;
+        let portal = if current.is_some() && check_portal { check_captive_portal() } else { None };
+
+        RefreshResult {
+            networkmanager_running: true,
+            wifi_enabled,
+            current,
+            networks,
+            band,
+            details,
+            ethernet,
+            vpn_connections,
+            portal,
+        }
+    }
+
+    fn apply_refresh(&mut self, result: RefreshResult) {
+        self.refreshing = false;
+        let first_load = !self.loaded;
+        self.loaded = true;
+
+        self.networkmanager_running = result.networkmanager_running;
+        if !self.networkmanager_running {
+            // Every other nmcli call would fail, so show the daemon state instead of stale data
+            self.connection_state = ConnectionState::Disconnected;
+            self.known_networks.clear();
+            self.available_networks.clear();
+            self.connection_details = ConnectionDetails::default();
+            self.ethernet = None;
+            self.vpn_connections.clear();
+            self.throughput = None;
+            self.login_page = None;
+            return;
+        }
+
+        self.ethernet = result.ethernet;
+        self.vpn_connections = result.vpn_connections;
+        self.wifi_enabled = result.wifi_enabled;
+
+        // An attempt holds its state until nmcli reports the network up or it times out
+        let mut still_connecting = false;
+        if let ConnectionState::Connecting(ssid, started) = &self.connection_state {
+            if result.current.as_ref() != Some(ssid) {
+                if started.elapsed() < CONNECT_TIMEOUT {
+                    still_connecting = true;
+                } else {
+                    eprintln!("Warning: Timed out connecting to {}", ssid);
+                    self.action_error = Some((ssid.clone(), "Timed out while connecting".to_string()));
+                    self.connection_state = ConnectionState::Disconnected;
+                }
+            }
+        }
+
+        let connection_changed = !still_connecting && match (&self.connection_state, &result.current) {
             (ConnectionState::Connected(old), Some(new)) => old != new,
             (ConnectionState::Connected(_), None) => true,
+            (ConnectionState::Connecting(..), _) => true,
             (ConnectionState::Disconnected, Some(_)) => true,
             _ => false,
         };
         
+        // A connection that came up makes an earlier failure moot
+        if connection_changed && result.current.is_some() {
+            self.action_error = None;
+        }
+        // A new connection needs its own check
+        if connection_changed {
+            self.login_page = None;
+            self.portal_checked = false;
+        }
+        if let Some(portal) = result.portal {
+            self.portal_checked = true;
+            self.login_page = match portal {
+                PortalCheck::Online => None,
+                PortalCheck::LoginRequired(page) => Some(page),
+            };
+        }
+
         // Update connection state
-        if let Some(current) = current {
-            self.connection_state = ConnectionState::Connected(current);
-        } else {
-            self.connection_state = ConnectionState::Disconnected;
+        if !still_connecting {
+            if let Some(current) = result.current {
+                self.connection_state = ConnectionState::Connected(current);
+            } else {
+                self.connection_state = ConnectionState::Disconnected;
+            }
         }
-        
-        // Only fetch all networks if connection changed or none are available
-        if connection_changed || self.known_networks.is_empty() && self.available_networks.is_empty() {
-            let (known, available) = Self::get_networks();
+
+        if let Some((known, available)) = result.networks {
             self.known_networks = known;
             self.available_networks = available;
+            // Collapse only once the expanded network is gone from both lists
+            if let Some(expanded) = &self.expanded_network {
+                if !self.known_networks.iter().chain(&self.available_networks).any(|n| &n.ssid == expanded) {
+                    self.expanded_network = None;
+                }
+            }
+        }
+        if let Some(band) = result.band {
+            self.connected_band = band;
+        }
+        if let Some(details) = result.details {
+            self.connection_details = details;
+        }
+
+        if first_load {
+            // The state at startup is the baseline, not a change worth notifying
+            self.notified_state = Some(self.connection_message());
+        } else if self.options.notify {
+            self.notify_connection_change(connection_changed);
         }
-        self.last_update = Instant::now();
     }
 
-    pub fn colors(&self) -> &super::Colors {
-        &self.colors
+    /// Measures the rates of the connected interface, Wi-Fi first, then wired
+    fn sample_throughput(&mut self) {
+        let device = self.connection_details.device.clone()
+            .or_else(|| self.ethernet.as_ref().map(|ethernet| ethernet.device.clone()));
+        let sample = device.as_deref().and_then(TrafficSample::read);
+
+        self.throughput = match (&sample, &self.traffic_sample) {
+            (Some(sample), Some(previous)) => sample.rates_since(previous),
+            _ => None,
+        };
+        self.traffic_sample = sample;
     }
 
     fn get_signal_icon(strength: i32) -> &'static str {
@@ -203,13 +1245,133 @@ impl NetworkWidget {
         else { egui_phosphor::regular::WIFI_X }
     }
     
-    // Helper function to get button text and icon
-    fn get_button_config(button_type: &str) -> String {
+    /// Draws the signal indicator in the configured style
+    fn show_signal(&self, ui: &mut Ui, strength: i32, color: Color32) {
+        if self.options.signal_style == SignalStyle::Glyph {
+            ui.label(RichText::new(Self::get_signal_icon(strength)).color(color).size(20.0));
+            return;
+        }
+
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(20.0), Sense::hover());
+        let track = self.colors.outline.gamma_multiply(0.4);
+        match self.options.signal_style {
+            SignalStyle::Bars => Self::paint_signal_bars(ui.painter(), rect, strength, color, track),
+            _ => Self::paint_signal_ring(ui.painter(), rect, strength, color, track),
+        }
+    }
+
+    /// Four-bar meter, one bar lit per 25% of signal
+    fn paint_signal_bars(painter: &Painter, rect: Rect, strength: i32, color: Color32, track: Color32) {
+        let bars = 4;
+        let gap = 2.0;
+        let bar_width = (rect.width() - gap * (bars - 1) as f32) / bars as f32;
+        let lit = ((strength.clamp(0, 100) + 24) / 25) as usize;
+
+        for i in 0..bars {
+            let height = rect.height() * (i + 1) as f32 / bars as f32;
+            let left = rect.left() + i as f32 * (bar_width + gap);
+            let bar = Rect::from_min_max(
+                eframe::egui::pos2(left, rect.bottom() - height),
+                eframe::egui::pos2(left + bar_width, rect.bottom()),
+            );
+            painter.rect_filled(bar, 1.0, if i < lit { color } else { track });
+        }
+    }
+
+    /// Circular ring filled clockwise from the top by signal percentage
+    fn paint_signal_ring(painter: &Painter, rect: Rect, strength: i32, color: Color32, track: Color32) {
+        let center = rect.center();
+        let radius = rect.width().min(rect.height()) / 2.0 - 2.0;
+        painter.circle_stroke(center, radius, Stroke::new(2.5, track));
+
+        let fraction = strength.clamp(0, 100) as f32 / 100.0;
+        if fraction > 0.0 {
+            let segments = (48.0 * fraction).ceil().max(2.0) as usize;
+            let start = -std::f32::consts::FRAC_PI_2;
+            let points = (0..=segments)
+                .map(|i| {
+                    let angle = start + std::f32::consts::TAU * fraction * i as f32 / segments as f32;
+                    center + radius * Vec2::angled(angle)
+                })
+                .collect();
+            painter.add(Shape::line(points, Stroke::new(2.5, color)));
+        }
+    }
+
+    // Helper function to get button icon and label
+    fn get_button_config(button_type: &str) -> (&'static str, &'static str) {
         match button_type {
-            "connect" => egui_phosphor::regular::PLUG.to_string(),
-            "disconnect" => egui_phosphor::regular::PLUG_CHARGING.to_string(),
-            "forget" => egui_phosphor::regular::TRASH.to_string(),
-            _ => egui_phosphor::regular::WARNING.to_string(),
+            "connect" => (egui_phosphor::regular::PLUG, "Connect"),
+            "disconnect" => (egui_phosphor::regular::PLUG_CHARGING, "Disconnect"),
+            "forget" => (egui_phosphor::regular::TRASH, "Forget"),
+            "confirm_forget" => (egui_phosphor::regular::WARNING, "Confirm"),
+            _ => (egui_phosphor::regular::WARNING, ""),
+        }
+    }
+
+    fn panel_radius(&self) -> u8 {
+        super::corner_radius(self.options.rounding, 12)
+    }
+
+    /// Buttons and highlights follow --rounding down to sharp corners, but don't
+    /// grow past their default so they stay buttons rather than pills
+    fn control_radius(&self) -> u8 {
+        super::corner_radius(self.options.rounding, 6).min(6)
+    }
+
+    /// Width of the expanded row's action buttons
+    fn button_width(&self) -> f32 {
+        if self.options.labeled_buttons { 104.0 } else { 36.0 }
+    }
+
+    /// Button text, with the label next to the glyph when --labeled-buttons is set
+    fn button_text(&self, button_type: &str, color: Color32) -> RichText {
+        let (icon, label) = Self::get_button_config(button_type);
+        if self.options.labeled_buttons {
+            RichText::new(format!("{} {}", icon, label)).color(color).size(14.0)
+        } else {
+            RichText::new(icon).color(color).size(18.0)
+        }
+    }
+
+    fn is_forget_pending(&self, ssid: &str) -> bool {
+        self.pending_forget.as_ref()
+            .is_some_and(|(pending, at)| pending == ssid && at.elapsed() < FORGET_CONFIRM_TIMEOUT)
+    }
+
+    /// First call arms forgetting `ssid`; a second call before the timeout deletes it
+    fn request_forget(&mut self, ssid: &str) {
+        if self.is_forget_pending(ssid) {
+            self.pending_forget = None;
+            self.run_action(ssid, &["connection", "delete", ssid]);
+        } else {
+            self.pending_forget = Some((ssid.to_string(), Instant::now()));
+        }
+    }
+
+    /// Forget button that needs a second press to confirm
+    fn show_forget_button(&mut self, ui: &mut Ui, rect: Rect, ssid: &str) {
+        let pending = self.is_forget_pending(ssid);
+        let (kind, color) = if pending {
+            ("confirm_forget", self.colors.primary_fixed_dim)
+        } else {
+            ("forget", self.colors.outline)
+        };
+
+        let mut response = ui.put(
+            rect,
+            Button::new(self.button_text(kind, color))
+            .fill(self.colors.surface_container)
+            .corner_radius(self.control_radius())
+            .stroke(Stroke::new(1.5, color))
+        );
+        if pending {
+            response = response.on_hover_text("Click again to forget");
+            // Repaint once the confirmation window lapses so the button resets
+            ui.ctx().request_repaint_after(FORGET_CONFIRM_TIMEOUT);
+        }
+        if response.clicked() {
+            self.request_forget(ssid);
         }
     }
 
@@ -221,63 +1383,645 @@ impl NetworkWidget {
         egui_phosphor::regular::LOCK
     }
 
-    pub fn show(&mut self, ui: &mut Ui) {
+    /// Summary glyph, color and label for the overall connection state
+    fn get_status_summary(&self) -> (&'static str, Color32, String) {
+        if !self.networkmanager_running {
+            return (
+                egui_phosphor::regular::WARNING,
+                self.colors.outline,
+                "NetworkManager not running".to_string(),
+            );
+        }
+        let (icon, color, label) = match &self.connection_state {
+            ConnectionState::Connected(ssid) if self.login_page.is_some() => (
+                egui_phosphor::regular::WARNING,
+                self.colors.primary_fixed_dim,
+                format!("{} · login required", ssid),
+            ),
+            ConnectionState::Connected(ssid) => (
+                egui_phosphor::regular::WIFI_HIGH,
+                self.colors.primary_fixed_dim,
+                ssid.clone(),
+            ),
+            ConnectionState::Connecting(ssid, _) => (
+                egui_phosphor::regular::WIFI_MEDIUM,
+                self.colors.outline,
+                format!("Connecting to {}", ssid),
+            ),
+            ConnectionState::Disconnected if self.ethernet.is_some() => (
+                egui_phosphor::regular::PLUGS_CONNECTED,
+                self.colors.primary_fixed_dim,
+                "Ethernet".to_string(),
+            ),
+            ConnectionState::Disconnected if !self.wifi_enabled => (
+                egui_phosphor::regular::WIFI_X,
+                self.colors.outline,
+                "Wi-Fi off".to_string(),
+            ),
+            ConnectionState::Disconnected => (
+                egui_phosphor::regular::WIFI_X,
+                self.colors.outline,
+                "Disconnected".to_string(),
+            ),
+        };
+
+        // An active VPN carries the traffic, so it leads the summary
+        match self.vpn_connections.iter().find(|vpn| vpn.active) {
+            Some(vpn) => (
+                egui_phosphor::regular::SHIELD_CHECK,
+                self.colors.primary_fixed_dim,
+                format!("{} · {}", vpn.name, label),
+            ),
+            None => (icon, color, label),
+        }
+    }
+
+    /// Label that copies its text to the clipboard when clicked
+    fn copyable_label(&mut self, ui: &mut Ui, text: &str, color: Color32, size: f32) {
+        let just_copied = self.copied.as_ref()
+            .is_some_and(|(copied, at)| copied == text && at.elapsed() < COPY_FEEDBACK);
+
+        let label = if just_copied {
+            RichText::new(format!("{} Copied", egui_phosphor::regular::CHECK)).color(self.colors.primary_fixed_dim)
+        } else {
+            RichText::new(text).color(color)
+        };
+
+        let response = ui.add(Label::new(label.size(size)).sense(Sense::click()))
+            .on_hover_cursor(CursorIcon::Copy);
+        if response.clicked() {
+            ui.ctx().copy_text(text.to_string());
+            self.copied = Some((text.to_string(), Instant::now()));
+            ui.ctx().request_repaint_after(COPY_FEEDBACK);
+        }
+    }
+
+    /// Wired connection row above the Wi-Fi list; expands to its address and a Disconnect button
+    fn show_ethernet_row(&mut self, ui: &mut Ui, ethernet: &EthernetConnection) {
+        let row_height = 32.0;
+        let response = ui.add_sized(
+            [ui.available_width(), row_height],
+            Button::new("").fill(Color32::TRANSPARENT).frame(false),
+        );
+        ui.allocate_new_ui(eframe::egui::UiBuilder::new().max_rect(response.rect), |ui| {
+            ui.horizontal(|ui| {
+                ui.add_space(8.0);
+                ui.label(RichText::new(egui_phosphor::regular::PLUGS_CONNECTED).color(self.colors.primary_fixed_dim).size(20.0));
+                ui.add_space(4.0);
+                ui.label(RichText::new("Ethernet").color(self.colors.primary_fixed_dim).size(16.0));
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    ui.add_space(8.0);
+                    ui.label(RichText::new(&ethernet.device).color(self.colors.outline).size(14.0));
+                });
+            });
+        });
+        if response.clicked() {
+            self.ethernet_expanded = !self.ethernet_expanded;
+        }
+
+        if self.ethernet_expanded {
+            ui.horizontal(|ui| {
+                ui.set_height(32.0);
+                ui.add_space(8.0);
+                ui.label(RichText::new("IP").color(self.colors.outline).size(14.0));
+                match &ethernet.address {
+                    Some(address) => self.copyable_label(ui, address, self.colors.on_surface_variant, 14.0),
+                    None => {
+                        ui.label(RichText::new("No address").color(self.colors.outline).size(14.0));
+                    }
+                }
+
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    ui.add_space(8.0);
+                    if ui.add_sized(
+                        [self.button_width(), 32.0],
+                        Button::new(self.button_text("disconnect", self.colors.primary_fixed_dim))
+                        .fill(self.colors.surface_container)
+                        .corner_radius(self.control_radius())
+                        .stroke(Stroke::new(1.5, self.colors.primary_fixed_dim))
+                    ).clicked() {
+                        self.run_action(&ethernet.device, &["device", "disconnect", &ethernet.device]);
+                    }
+                });
+            });
+        }
+
+        self.show_action_error(ui, &ethernet.device);
+        ui.add_space(4.0);
+    }
+
+    /// Why the last action on `key` (an SSID, device or VPN name) failed, if it did
+    fn show_action_error(&self, ui: &mut Ui, key: &str) {
+        if let Some((_, message)) = self.action_error.as_ref().filter(|(failed, _)| failed == key) {
+            ui.horizontal(|ui| {
+                ui.add_space(8.0);
+                ui.add(Label::new(RichText::new(message).color(super::WARNING_COLOR).size(12.0)).wrap());
+            });
+        }
+    }
+
+    /// VPN profiles below the Wi-Fi list; clicking one brings it up or down
+    fn show_vpn_section(&mut self, ui: &mut Ui) {
+        let connections = self.vpn_connections.clone();
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            ui.add_space(8.0);
+            ui.label(RichText::new("VPN").color(self.colors.outline).size(14.0));
+        });
+
+        for connection in &connections {
+            let (icon, color) = if connection.active {
+                (egui_phosphor::regular::SHIELD_CHECK, self.colors.primary_fixed_dim)
+            } else {
+                (egui_phosphor::regular::SHIELD, self.colors.on_surface_variant)
+            };
+
+            let response = ui.add_sized(
+                [ui.available_width(), 32.0],
+                Button::new("").fill(Color32::TRANSPARENT).frame(false),
+            ).on_hover_text(if connection.active { "Disconnect" } else { "Connect" });
+            ui.allocate_new_ui(eframe::egui::UiBuilder::new().max_rect(response.rect), |ui| {
+                ui.horizontal(|ui| {
+                    ui.add_space(8.0);
+                    ui.label(RichText::new(icon).color(color).size(20.0));
+                    ui.add_space(4.0);
+                    ui.label(RichText::new(&connection.name).color(color).size(16.0));
+                });
+            });
+            if response.clicked() {
+                let verb = if connection.active { "down" } else { "up" };
+                self.run_action(&connection.name, &["connection", verb, &connection.name]);
+            }
+
+            self.show_action_error(ui, &connection.name);
+        }
+    }
+
+    /// Entry at the bottom of the list that opens a form for joining a hidden network
+    fn show_hidden_network_entry(&mut self, ui: &mut Ui) {
+        ui.add_space(8.0);
+        let response = ui.add_sized(
+            [ui.available_width(), 32.0],
+            Button::new("").fill(Color32::TRANSPARENT).frame(false),
+        );
+        ui.allocate_new_ui(eframe::egui::UiBuilder::new().max_rect(response.rect), |ui| {
+            ui.horizontal(|ui| {
+                ui.add_space(8.0);
+                ui.label(RichText::new(egui_phosphor::regular::EYE_SLASH).color(self.colors.outline).size(20.0));
+                ui.add_space(4.0);
+                ui.label(RichText::new("Connect to hidden network").color(self.colors.on_surface_variant).size(16.0));
+            });
+        });
+        if response.clicked() {
+            self.hidden_entry = match self.hidden_entry {
+                Some(_) => None,
+                None => Some((String::new(), String::new())),
+            };
+        }
+
+        let button_width = self.button_width();
+        let button_text = self.button_text("connect", self.colors.primary_fixed_dim);
+        let control_radius = self.control_radius();
+        let mut submitted = false;
+        let Some((ssid, password)) = &mut self.hidden_entry else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.add_space(8.0);
+            ui.add_sized(
+                [ui.available_width() - 8.0, 32.0],
+                TextEdit::singleline(ssid)
+                    .hint_text("Network name")
+                    .text_color(self.colors.on_surface_variant)
+                    .background_color(self.colors.surface_container)
+                    .vertical_align(Align::Center),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.add_space(8.0);
+            let edit = ui.add_sized(
+                [ui.available_width() - button_width - 18.0, 32.0],
+                Self::password_edit(&self.colors, password),
+            );
+            submitted = edit.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            submitted |= ui.add_sized(
+                [button_width, 32.0],
+                Button::new(button_text)
+                .fill(self.colors.surface_container)
+                .corner_radius(control_radius)
+                .stroke(Stroke::new(1.5, self.colors.primary_fixed_dim))
+            ).clicked();
+        });
+
+        let ssid = ssid.trim().to_string();
+        if submitted && !ssid.is_empty() {
+            // Open hidden networks are joined without a password
+            let password = std::mem::take(password);
+            self.connect_new(&ssid, Some(password.as_str()).filter(|password| !password.is_empty()), true);
+        }
+        self.show_action_error(ui, &ssid);
+    }
+
+    /// Shown instead of the list when there is nothing to display
+    fn show_empty_state(&mut self, ui: &mut Ui) {
+        ui.add_space(48.0);
+        if !self.networkmanager_running {
+            ui.vertical_centered(|ui| {
+                ui.label(RichText::new(egui_phosphor::regular::WARNING).color(self.colors.outline).size(32.0));
+                ui.add_space(4.0);
+                ui.label(RichText::new("NetworkManager is not running").color(self.colors.on_surface_variant).size(16.0));
+                ui.add_space(12.0);
+
+                if ui.add(
+                    Button::new(RichText::new("Start NetworkManager").color(self.colors.primary_fixed_dim).size(14.0))
+                    .fill(self.colors.surface_container)
+                    .corner_radius(self.control_radius())
+                    .stroke(Stroke::new(1.5, self.colors.primary_fixed_dim))
+                    .min_size(Vec2::new(120.0, 32.0))
+                ).on_hover_text(&self.options.nm_start_command).clicked() {
+                    super::spawn_reaped(Command::new("sh").args(["-c", &self.options.nm_start_command])).ok();
+                }
+            });
+            return;
+        }
+        if !self.wifi_enabled {
+            ui.vertical_centered(|ui| {
+                ui.label(RichText::new(egui_phosphor::regular::WIFI_X).color(self.colors.outline).size(32.0));
+                ui.add_space(4.0);
+                ui.label(RichText::new("Wi-Fi disabled").color(self.colors.on_surface_variant).size(16.0));
+                ui.add_space(12.0);
+
+                // Filled rather than outlined; it's the only thing to do here
+                if ui.add(
+                    Button::new(RichText::new("Enable Wi-Fi").color(self.colors.on_primary_fixed).size(16.0))
+                    .fill(self.colors.primary_fixed_dim)
+                    .corner_radius(self.control_radius())
+                    .min_size(Vec2::new(160.0, 40.0))
+                ).clicked() {
+                    self.set_wifi_enabled(true);
+                }
+                self.show_action_error(ui, RADIO_ACTION);
+            });
+            return;
+        }
+        ui.vertical_centered(|ui| {
+            ui.label(RichText::new(egui_phosphor::regular::WIFI_SLASH).color(self.colors.outline).size(32.0));
+            ui.add_space(4.0);
+            ui.label(RichText::new("No networks found").color(self.colors.on_surface_variant).size(16.0));
+            ui.add_space(12.0);
+
+            if ui.add(
+                Button::new(RichText::new("Rescan").color(self.colors.primary_fixed_dim).size(14.0))
+                .fill(self.colors.surface_container)
+                .corner_radius(self.control_radius())
+                .stroke(Stroke::new(1.5, self.colors.primary_fixed_dim))
+                .min_size(Vec2::new(120.0, 32.0))
+            ).clicked() {
+                self.rescan();
+            }
+        });
+    }
+
+    /// Visible networks in display order, paired with whether each is connected
+    fn networks_to_show(&self) -> Vec<(WifiNetwork, bool)> {
+        let mut networks_to_show = Vec::new();
+        // Networks seen before the radio went off are no longer reachable
+        if !self.wifi_enabled {
+            return networks_to_show;
+        }
+        let current_network = if let ConnectionState::Connected(ref current) = self.connection_state {
+            Some(current.clone())
+        } else {
+            None
+        };
+        
+        // The expanded network stays put while its signal flickers to 0, so the
+        // panel doesn't vanish mid-interaction
+        let in_range = |network: &WifiNetwork| {
+            network.signal_strength > 0 || self.expanded_network.as_ref() == Some(&network.ssid)
+        };
+
+        // Add connected network first
+        if let Some(current) = &current_network {
+            if let Some(network) = self.known_networks.iter()
+                .find(|n| &n.ssid == current && in_range(n))
+                .or_else(|| self.available_networks.iter()
+                    .find(|n| &n.ssid == current && in_range(n))) {
+                networks_to_show.push((network.clone(), true));
+            }
+        }
+
+        // Add known networks
+        for network in &self.known_networks {
+            if Some(&network.ssid) != current_network.as_ref() && in_range(network) {
+                networks_to_show.push((network.clone(), false));
+            }
+        }
+
+        // Add available networks
+        for network in &self.available_networks {
+            let strong_enough = network.signal_strength >= self.options.min_signal
+                || self.expanded_network.as_ref() == Some(&network.ssid);
+            if Some(&network.ssid) != current_network.as_ref() && in_range(network) && strong_enough {
+                networks_to_show.push((network.clone(), false));
+            }
+        }
+
+        // Starred networks follow the connected one; the sort is stable so
+        // both groups keep their order
+        let start = usize::from(networks_to_show.first().is_some_and(|(_, connected)| *connected));
+        networks_to_show[start..].sort_by_key(|(network, _)| !self.favorites.contains(&network.ssid));
+
+        networks_to_show
+    }
+
+    /// Width of the list layout, measured from the visible SSIDs with --network-autowidth
+    fn list_width(&self, ui: &Ui, networks: &[(WifiNetwork, bool)]) -> f32 {
+        if !self.options.autowidth {
+            return LIST_WIDTH;
+        }
+
+        let widest_ssid = networks.iter()
+            .map(|(network, _)| ui.fonts(|fonts| {
+                fonts.layout_no_wrap(network.ssid.clone(), FontId::proportional(16.0), Color32::WHITE).size().x
+            }))
+            .fold(0.0, f32::max);
+
+        // The expanded row's security label and action buttons need room too
+        let buttons_row = 108.0 + self.button_width() * 2.0 + 10.0 + 16.0;
+
+        ((widest_ssid + ROW_CHROME_WIDTH).max(buttons_row) + LIST_MARGIN)
+            .clamp(AUTOWIDTH_MIN, AUTOWIDTH_MAX)
+    }
+
+    /// Compact bar layout: the strongest few networks side by side
+    fn show_row(&mut self, ui: &mut Ui) {
+        let networks = self.networks_to_show();
+
+        let response = Frame::new()
+            .fill(self.colors.surface_container_low)
+            .corner_radius(self.panel_radius())
+            .inner_margin(8.0)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.set_height(ROW_LAYOUT_HEIGHT - 16.0);
+                    ui.spacing_mut().item_spacing = Vec2::new(12.0, 0.0);
+
+                    if !self.loaded {
+                        ui.add(Spinner::new().color(self.colors.outline));
+                    } else if networks.is_empty() {
+                        let (icon, color, label) = self.get_status_summary();
+                        ui.label(RichText::new(format!("{} {}", icon, label)).color(color).size(14.0));
+                    }
+
+                    for (network, is_connected) in networks.iter().take(ROW_LAYOUT_NETWORKS) {
+                        let color = if *is_connected {
+                            self.colors.primary_fixed_dim
+                        } else {
+                            self.colors.on_surface_variant
+                        };
+                        let mut ssid: String = network.ssid.chars().take(ROW_LAYOUT_SSID_CHARS).collect();
+                        if network.ssid.chars().count() > ROW_LAYOUT_SSID_CHARS {
+                            ssid.push('…');
+                        }
+
+                        let label = format!("{} {}", Self::get_signal_icon(network.signal_strength), ssid);
+                        if ui.add(
+                            Button::new(RichText::new(label).color(color).size(14.0))
+                            .fill(Color32::TRANSPARENT)
+                            .frame(false)
+                        ).clicked() {
+                            // Open the full list with this network expanded
+                            self.expanded_network = Some(network.ssid.clone());
+                            self.row_list_open = true;
+                        }
+                    }
+                });
+            })
+            .response;
+
+        // Account for the outer frame margin added in main
+        self.size = response.rect.size() + Vec2::splat(12.0);
+    }
+
+    fn show_status_header(&mut self, ui: &mut Ui) {
+        let (icon, color, label) = self.get_status_summary();
+        ui.horizontal(|ui| {
+            ui.set_height(STATUS_HEADER_HEIGHT);
+            ui.add_space(8.0);
+            ui.label(RichText::new(icon).color(color).size(20.0));
+            ui.add_space(4.0);
+            ui.label(RichText::new(label).color(color).size(14.0));
+
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                ui.add_space(8.0);
+
+                // The list was opened from the compact row; offer a way back
+                if self.options.layout == NetworkLayout::Row {
+                    if ui.add(
+                        Button::new(RichText::new(egui_phosphor::regular::CARET_UP).color(self.colors.outline).size(18.0))
+                        .fill(Color32::TRANSPARENT)
+                        .frame(false)
+                    ).clicked() {
+                        self.row_list_open = false;
+                    }
+                    ui.add_space(4.0);
+                }
+
+                // Rescan, held back for a few seconds after each press
+                if self.networkmanager_running && self.wifi_enabled {
+                    let debounced = self.is_rescan_debounced();
+                    let response = ui.add_enabled(
+                        !debounced,
+                        Button::new(RichText::new(egui_phosphor::regular::ARROWS_CLOCKWISE).color(self.colors.outline).size(18.0))
+                        .fill(Color32::TRANSPARENT)
+                        .frame(false)
+                    ).on_hover_text("Rescan");
+                    if response.clicked() {
+                        self.rescan();
+                    }
+                    if debounced {
+                        // Repaint when the button can be pressed again
+                        ui.ctx().request_repaint_after(RESCAN_DEBOUNCE);
+                    }
+                    ui.add_space(4.0);
+                }
+
+                // Radio toggle, lit while Wi-Fi is on
+                if self.networkmanager_running {
+                    let (icon, color, hint) = if self.wifi_enabled {
+                        (egui_phosphor::regular::WIFI_HIGH, self.colors.primary_fixed_dim, "Turn Wi-Fi off")
+                    } else {
+                        (egui_phosphor::regular::WIFI_X, self.colors.outline, "Turn Wi-Fi on")
+                    };
+                    if ui.add(
+                        Button::new(RichText::new(icon).color(color).size(18.0))
+                        .fill(self.colors.surface_container)
+                        .corner_radius(self.control_radius())
+                        .stroke(Stroke::new(1.5, color))
+                    ).on_hover_text(hint).clicked() {
+                        self.set_wifi_enabled(!self.wifi_enabled);
+                    }
+                }
+            });
+        });
+    }
+}
+
+impl Panel for NetworkWidget {
+    fn shortcuts(&self) -> Vec<(String, String)> {
+        Self::key_table().iter().map(Shortcut::help).collect()
+    }
+
+    fn next_update(&self) -> Option<Duration> {
+        Some(self.options.refresh_interval.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        self.last_update.elapsed() > self.options.refresh_interval || self.refresh_ready.load(Ordering::Acquire)
+    }
+
+    /// Applies finished refreshes and starts the next one when it's due
+    fn update(&mut self) {
+        self.refresh_ready.store(false, Ordering::Release);
+        // Whatever an action changed, succeeded or not, shows up in the next refresh;
+        // one already running may have started too early, so this waits for it
+        if self.collect_action_results() {
+            self.refetch_networks = true;
+        }
+        while let Ok(result) = self.refresh_results.try_recv() {
+            self.apply_refresh(result);
+        }
+
+        let due = self.refetch_networks || self.last_update.elapsed() > self.options.refresh_interval;
+        if !self.refreshing && due {
+            self.start_refresh();
+        }
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        // Lets the refresh thread wake the UI when its result is in
+        self.repaint_ctx.get_or_init(|| ui.ctx().clone());
+
+        if self.options.layout == NetworkLayout::Row && !self.row_list_open {
+            self.show_row(ui);
+            return;
+        }
+
         let mut size = self.size;
+        let width = self.list_width(ui, &self.networks_to_show());
 
         // Main panel
         Frame::new()
             .fill(self.colors.surface_container_low)
-            .corner_radius(12)
+            .corner_radius(self.panel_radius())
             .inner_margin(8.0)
             .show(ui, |ui| {
                 // Set fixed width and height for the main panel
-                ui.set_width(400.0); // Wider to accommodate scrollbar
+                ui.set_width(width); // Wider to accommodate scrollbar
                 ui.set_min_height(434.0);
 
+                // Overall connection summary
+                self.show_status_header(ui);
+                let throughput_height = if self.throughput.is_some() { THROUGHPUT_HEIGHT } else { 0.0 };
+                if let Some((rx, tx)) = self.throughput {
+                    ui.horizontal(|ui| {
+                        ui.set_height(THROUGHPUT_HEIGHT);
+                        ui.add_space(8.0);
+                        let text = format!("↓ {}  ↑ {}", format_rate(rx), format_rate(tx));
+                        ui.label(RichText::new(text).color(self.colors.outline).size(13.0));
+                    });
+                }
+                self.show_action_error(ui, RESCAN_ACTION);
+
+                // Behind a captive portal the connection is up but not online yet
+                let login_height = if self.login_page.is_some() { LOGIN_PROMPT_HEIGHT } else { 0.0 };
+                if let Some(page) = &self.login_page {
+                    ui.horizontal(|ui| {
+                        ui.set_height(LOGIN_PROMPT_HEIGHT);
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("This network requires a login").color(self.colors.on_surface_variant).size(14.0));
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            ui.add_space(8.0);
+                            if ui.add(
+                                Button::new(RichText::new("Open login page").color(self.colors.on_primary_fixed).size(14.0))
+                                .fill(self.colors.primary_fixed_dim)
+                                .corner_radius(self.control_radius())
+                            ).on_hover_text(page).clicked() {
+                                super::spawn_reaped(Command::new("xdg-open").arg(page)).ok();
+                            }
+                        });
+                    });
+                }
+
                 // Combined networks list
                 ScrollArea::vertical()
                     .auto_shrink([false; 2])
-                    .max_height(434.0 - 16.0) // Account for padding
+                    .max_height(434.0 - 16.0 - STATUS_HEADER_HEIGHT - throughput_height - login_height) // Account for padding and header
                     .show(ui, |ui| {
-                        ui.set_width(384.0); // Wider content area for proper layout
+                        ui.set_width(width - LIST_MARGIN); // Wider content area for proper layout
                         
-                        // Collect networks to display first
-                        let mut networks_to_show = Vec::new();
-                        let current_network = if let ConnectionState::Connected(ref current) = self.connection_state {
-                            Some(current.clone())
-                        } else {
-                            None
-                        };
-                        
-                        // Add connected network first
-                        if let Some(current) = &current_network {
-                            if let Some(network) = self.known_networks.iter()
-                                .find(|n| &n.ssid == current && n.signal_strength > 0)
-                                .or_else(|| self.available_networks.iter()
-                                    .find(|n| &n.ssid == current && n.signal_strength > 0)) {
-                                networks_to_show.push((network.clone(), true));
-                            }
+                        // A wired connection sits above the Wi-Fi networks
+                        if let Some(ethernet) = self.ethernet.clone() {
+                            self.show_ethernet_row(ui, &ethernet);
                         }
 
-                        // Add known networks
-                        for network in &self.known_networks {
-                            if Some(&network.ssid) != current_network.as_ref() && network.signal_strength > 0 {
-                                networks_to_show.push((network.clone(), false));
-                            }
+                        // Collect networks to display first
+                        let networks_to_show = self.networks_to_show();
+
+                        if !self.loaded {
+                            ui.add_space(48.0);
+                            ui.vertical_centered(|ui| {
+                                ui.add(Spinner::new().size(32.0).color(self.colors.outline));
+                            });
+                        } else if networks_to_show.is_empty() {
+                            self.show_empty_state(ui);
                         }
 
-                        // Add available networks
-                        for network in &self.available_networks {
-                            if Some(&network.ssid) != current_network.as_ref() && network.signal_strength > 0 {
-                                networks_to_show.push((network.clone(), false));
+                        // Keyboard navigation: arrows move the selection, Enter expands,
+                        // Delete starts forgetting a known network
+                        let total = networks_to_show.len();
+                        self.selected_index = self.selected_index.filter(|_| total > 0).map(|i| i.min(total - 1));
+                        // Typing in the password field doesn't move the selection
+                        if total > 0 && !ui.ctx().wants_keyboard_input() {
+                            let table = Self::key_table();
+                            for action in ui.input(|i| shortcut::pressed(i, &table)) {
+                                let selected = self.selected_index.and_then(|i| networks_to_show.get(i));
+                                match (action, selected) {
+                                    (NetworkKey::Down, _) => {
+                                        self.selected_index = Some(self.selected_index.map_or(0, |i| (i + 1).min(total - 1)));
+                                    }
+                                    (NetworkKey::Up, _) => {
+                                        self.selected_index = Some(self.selected_index.map_or(0, |i| i.saturating_sub(1)));
+                                    }
+                                    (NetworkKey::Expand, Some((network, _))) => {
+                                        if self.expanded_network.as_ref() == Some(&network.ssid) {
+                                            self.expanded_network = None;
+                                        } else {
+                                            self.expanded_network = Some(network.ssid.clone());
+                                        }
+                                    }
+                                    (NetworkKey::Forget, Some((network, _))) if network.is_known => {
+                                        // Expand so the armed forget button is visible
+                                        let ssid = network.ssid.clone();
+                                        self.expanded_network = Some(ssid.clone());
+                                        self.request_forget(&ssid);
+                                    }
+                                    _ => {}
+                                }
                             }
                         }
 
                         // Now display all networks
-                        let total = networks_to_show.len();
                         for (idx, (network, is_connected)) in networks_to_show.into_iter().enumerate() {
                             let text = network.ssid.clone();
-                            let is_expanded = self.expanded_network.as_ref().map_or(false, |n| n == &network.ssid);
+                            let is_expanded = self.expanded_network.as_ref() == Some(&network.ssid);
+                            let is_selected = self.selected_index == Some(idx);
 
                             let color = if is_connected {
                                 self.colors.primary_fixed_dim
@@ -299,11 +2043,19 @@ impl NetworkWidget {
                                     
                                     // Overlay the content on top of the button
                                     let rect = button_response.rect;
-                                    ui.allocate_ui_at_rect(rect, |ui| {
+                                    if is_selected {
+                                        ui.painter().rect_filled(rect, self.control_radius(), self.colors.surface_container);
+                                    }
+                                    ui.allocate_new_ui(eframe::egui::UiBuilder::new().max_rect(rect), |ui| {
                                         ui.horizontal(|ui| {
                                             // Network name on the left
                                             ui.add_space(8.0);
-                                            ui.label(RichText::new(&text).color(color).size(16.0));
+                                            if is_connected && is_expanded {
+                                                // Click the SSID to copy it
+                                                self.copyable_label(ui, &text, color, 16.0);
+                                            } else {
+                                                ui.label(RichText::new(&text).color(color).size(16.0));
+                                            }
                                             
                                             // Push the remaining elements to the right
                                             ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
@@ -313,10 +2065,35 @@ impl NetworkWidget {
                                                     ui.label(RichText::new(Self::get_unknown_indicator()).color(self.colors.outline).size(20.0));
                                                     ui.add_space(4.0);
                                                 }
+                                                // Spins while this network is being joined
+                                                if matches!(&self.connection_state, ConnectionState::Connecting(ssid, _) if *ssid == text) {
+                                                    ui.add(Spinner::new().size(16.0).color(self.colors.primary_fixed_dim));
+                                                    ui.add_space(4.0);
+                                                }
                                                 // Signal strength indicator
-                                                ui.label(RichText::new(Self::get_signal_icon(network.signal_strength))
-                                                    .color(if is_expanded { self.colors.primary_fixed_dim } else { color })
-                                                    .size(20.0));
+                                                self.show_signal(
+                                                    ui,
+                                                    network.signal_strength,
+                                                    if is_expanded { self.colors.primary_fixed_dim } else { color },
+                                                );
+                                                // Tells apart access points that share an SSID
+                                                if let Some(band) = network.band_tag() {
+                                                    ui.label(RichText::new(band).color(self.colors.outline).size(11.0));
+                                                }
+
+                                                // Star toggle, always visible on starred networks
+                                                let is_favorite = self.favorites.contains(&text);
+                                                if is_expanded || is_favorite {
+                                                    ui.add_space(6.0);
+                                                    let star_color = if is_favorite { self.colors.primary_fixed_dim } else { self.colors.outline };
+                                                    let star = ui.add(
+                                                        Label::new(RichText::new(egui_phosphor::regular::STAR).color(star_color).size(18.0))
+                                                            .sense(Sense::click())
+                                                    ).on_hover_text(if is_favorite { "Unpin from top" } else { "Pin to top" });
+                                                    if star.clicked() {
+                                                        self.toggle_favorite(&text);
+                                                    }
+                                                }
                                             });
                                         });
                                     });
@@ -332,11 +2109,11 @@ impl NetworkWidget {
                                         
                                         // Position the buttons directly
                                         let button_height = 32.0;
-                                        let button_width = 36.0;
+                                        let button_width = self.button_width();
                                         let spacing = 10.0;
                                         
                                         // Security indicator on the left
-                                        if !network.security.is_empty() && network.security != "none" {
+                                        if network.is_secured() {
                                             let security_rect = eframe::egui::Rect::from_min_size(
                                                 eframe::egui::pos2(
                                                     rect.left() + 8.0,  // Add left padding
@@ -374,13 +2151,24 @@ impl NetworkWidget {
                                                 &network.security
                                             };
                                             
-                                            ui.allocate_ui_at_rect(security_text_rect, |ui| {
+                                            ui.allocate_new_ui(eframe::egui::UiBuilder::new().max_rect(security_text_rect), |ui| {
                                                 ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
                                                     ui.label(RichText::new(security_text).color(self.colors.outline).size(14.0));
                                                 });
                                             });
                                         }
                                         
+                                        // Access point details, for troubleshooting roaming
+                                        if let Some(bssid) = &network.bssid {
+                                            ui.horizontal(|ui| {
+                                                ui.add_space(8.0);
+                                                ui.label(RichText::new(bssid).monospace().color(self.colors.outline).size(12.0));
+                                                if let Some(channel) = network.channel {
+                                                    ui.label(RichText::new(format!("CH {}", channel)).monospace().color(self.colors.outline).size(12.0));
+                                                }
+                                            });
+                                        }
+
                                         // Use the parent rect's width for proper alignment
                                         let right_edge = rect.right() - 8.0;  // Add right padding
                                         
@@ -407,29 +2195,61 @@ impl NetworkWidget {
                                             // Styled Disconnect button
                                             if ui.put(
                                                 disconnect_rect,
-                                                Button::new(RichText::new(Self::get_button_config("disconnect")).color(self.colors.primary_fixed_dim).size(18.0))
+                                                Button::new(self.button_text("disconnect", self.colors.primary_fixed_dim))
                                                 .fill(self.colors.surface_container)
-                                                .corner_radius(6)
+                                                .corner_radius(self.control_radius())
                                                 .stroke(eframe::egui::Stroke::new(1.5, self.colors.primary_fixed_dim))
                                             ).clicked() {
-                                                Command::new("nmcli")
-                                                    .args(["device", "disconnect", "wifi"])
-                                                    .spawn()
-                                                    .ok();
+                                                self.run_action(&text, &["device", "disconnect", "wifi"]);
                                             }
                                             
                                             // Styled Forget button
-                                            if ui.put(
-                                                forget_rect,
-                                                Button::new(RichText::new(Self::get_button_config("forget")).color(self.colors.outline).size(18.0))
-                                                .fill(self.colors.surface_container)
-                                                .corner_radius(6)
-                                                .stroke(eframe::egui::Stroke::new(1.5, self.colors.outline))
-                                            ).clicked() {
-                                                Command::new("nmcli")
-                                                    .args(["connection", "delete", &text])
-                                                    .spawn()
-                                                    .ok();
+                                            self.show_forget_button(ui, forget_rect, &text);
+
+                                            // Band restriction for the connected network
+                                            ui.horizontal(|ui| {
+                                                ui.add_space(8.0);
+                                                ui.label(RichText::new("Band").color(self.colors.outline).size(14.0));
+                                                for band in Band::ALL {
+                                                    let selected = self.connected_band == band;
+                                                    let label = RichText::new(band.label())
+                                                        .color(if selected { self.colors.primary_fixed_dim } else { self.colors.on_surface_variant })
+                                                        .size(14.0);
+                                                    if ui.selectable_label(selected, label).clicked() && !selected {
+                                                        self.set_band(&text, band);
+                                                    }
+                                                }
+                                            });
+
+                                            // Addresses, gateway and DNS servers, click to copy
+                                            let details = self.connection_details.clone();
+                                            if !details.addresses.is_empty() {
+                                                ui.horizontal(|ui| {
+                                                    ui.add_space(8.0);
+                                                    ui.label(RichText::new("IP").color(self.colors.outline).size(14.0));
+                                                    for address in &details.addresses {
+                                                        self.copyable_label(ui, address, self.colors.on_surface_variant, 14.0);
+                                                    }
+                                                });
+                                            }
+                                            ui.horizontal(|ui| {
+                                                ui.add_space(8.0);
+                                                ui.label(RichText::new("Gateway").color(self.colors.outline).size(14.0));
+                                                match &details.gateway {
+                                                    Some(gateway) => self.copyable_label(ui, gateway, self.colors.on_surface_variant, 14.0),
+                                                    None => {
+                                                        ui.label(RichText::new("No lease").color(self.colors.outline).size(14.0));
+                                                    }
+                                                }
+                                            });
+                                            if !details.dns.is_empty() {
+                                                ui.horizontal(|ui| {
+                                                    ui.add_space(8.0);
+                                                    ui.label(RichText::new("DNS").color(self.colors.outline).size(14.0));
+                                                    for server in &details.dns {
+                                                        self.copyable_label(ui, server, self.colors.on_surface_variant, 14.0);
+                                                    }
+                                                });
                                             }
                                         } else if network.is_known {
                                             // Known network - Connect and Forget
@@ -454,32 +2274,18 @@ impl NetworkWidget {
                                             // Styled Connect button
                                             if ui.put(
                                                 connect_rect,
-                                                Button::new(RichText::new(Self::get_button_config("connect")).color(self.colors.primary_fixed_dim).size(18.0))
+                                                Button::new(self.button_text("connect", self.colors.primary_fixed_dim))
                                                 .fill(self.colors.surface_container)
-                                                .corner_radius(6)
+                                                .corner_radius(self.control_radius())
                                                 .stroke(eframe::egui::Stroke::new(1.5, self.colors.primary_fixed_dim))
                                             ).clicked() {
-                                                Command::new("nmcli")
-                                                    .args(["connection", "up", &text])
-                                                    .spawn()
-                                                    .ok();
+                                                self.connect(&text, &["connection", "up", &text], None);
                                             }
                                             
                                             // Styled Forget button
-                                            if ui.put(
-                                                forget_rect,
-                                                Button::new(RichText::new(Self::get_button_config("forget")).color(self.colors.outline).size(18.0))
-                                                .fill(self.colors.surface_container)
-                                                .corner_radius(6)
-                                                .stroke(eframe::egui::Stroke::new(1.5, self.colors.outline))
-                                            ).clicked() {
-                                                Command::new("nmcli")
-                                                    .args(["connection", "delete", &text])
-                                                    .spawn()
-                                                    .ok();
-                                            }
+                                            self.show_forget_button(ui, forget_rect, &text);
                                         } else {
-                                            // Unknown network - Connect only
+                                            // Unknown network - Connect only, with a password for secured ones
                                             
                                             // Calculate position for right-aligned button
                                             let connect_rect = eframe::egui::Rect::from_min_size(
@@ -489,21 +2295,45 @@ impl NetworkWidget {
                                                 ),
                                                 eframe::egui::vec2(button_width, button_height)
                                             );
+
+                                            // Password field between the security label and the button
+                                            let secured = network.is_secured();
+                                            let mut submitted = false;
+                                            if secured {
+                                                let password_rect = eframe::egui::Rect::from_min_max(
+                                                    eframe::egui::pos2(rect.left() + 112.0, rect.max.y + 4.0),
+                                                    eframe::egui::pos2(right_edge - button_width - spacing, rect.max.y + 4.0 + button_height)
+                                                );
+                                                // Typing starts over when another network is expanded
+                                                if self.password_entry.as_ref().is_none_or(|(ssid, _)| ssid != &text) {
+                                                    self.password_entry = Some((text.clone(), String::new()));
+                                                }
+                                                if let Some((_, password)) = &mut self.password_entry {
+                                                    let edit = ui.put(password_rect, Self::password_edit(&self.colors, password));
+                                                    submitted = edit.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                                                }
+                                            }
                                             
                                             // Styled Connect button for unknown networks
-                                            if ui.put(
+                                            let clicked = ui.put(
                                                 connect_rect,
-                                                Button::new(RichText::new(Self::get_button_config("connect")).color(self.colors.primary_fixed_dim).size(18.0))
+                                                Button::new(self.button_text("connect", self.colors.primary_fixed_dim))
                                                 .fill(self.colors.surface_container)
-                                                .corner_radius(6)
+                                                .corner_radius(self.control_radius())
                                                 .stroke(eframe::egui::Stroke::new(1.5, self.colors.primary_fixed_dim))
-                                            ).clicked() {
-                                                // For new networks, we need to implement password dialog
-                                                // For now, we'll just print a message
-                                                eprintln!("Would connect to new network: {}", text);
+                                            ).clicked();
+                                            if clicked || submitted {
+                                                if !secured {
+                                                    self.connect_new(&text, None, false);
+                                                } else if let Some((_, password)) = self.password_entry.take().filter(|(_, password)| !password.is_empty()) {
+                                                    self.connect_new(&text, Some(&password), false);
+                                                }
                                             }
                                         }
                                     }
+
+                                    // Why the last action on this network failed
+                                    self.show_action_error(ui, &text);
                                     
                                     button_response
                                 });
@@ -523,20 +2353,79 @@ impl NetworkWidget {
                             }
                         }
 
+                        if !self.vpn_connections.is_empty() {
+                            self.show_vpn_section(ui);
+                        }
+
+                        if self.wifi_enabled {
+                            self.show_hidden_network_entry(ui);
+                        }
+
                         // Get the actual size needed for the content
-                        size = Vec2::new(400.0, 434.0); // Keep the fixed larger size
+                        size = Vec2::new(width, 434.0); // Keep the fixed height
                     });
             });
         
-        // Update our stored size
+        // Update our stored size; the app resizes the window to it
         self.size = size;
-        
-        // Send the size update
-        ui.ctx().send_viewport_cmd(ViewportCommand::InnerSize(size));
     }
 
-    // Add a getter for size
-    pub fn size(&self) -> Vec2 {
+    fn size(&self) -> Vec2 {
         self.size
     }
-}
\ No newline at end of file
+
+    fn min_size(&self) -> Vec2 {
+        match self.options.layout {
+            // The compact row can open into the full list
+            NetworkLayout::Row => Vec2::new(100.0, ROW_LAYOUT_HEIGHT),
+            // Width follows the SSIDs; height stays fixed
+            NetworkLayout::List if self.options.autowidth => Vec2::new(AUTOWIDTH_MIN, 434.0),
+            NetworkLayout::List => Vec2::new(LIST_WIDTH, 434.0),
+        }
+    }
+
+    fn max_size(&self) -> Vec2 {
+        match self.options.layout {
+            NetworkLayout::Row => Vec2::new(1024.0, 434.0),
+            NetworkLayout::List if self.options.autowidth => Vec2::new(AUTOWIDTH_MAX, 434.0),
+            NetworkLayout::List => Vec2::new(LIST_WIDTH, 434.0),
+        }
+    }
+
+    fn margin(&self) -> f32 {
+        6.0
+    }
+
+    fn corner_radius(&self) -> u8 {
+        8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_only_web_urls_as_login_pages() {
+        assert!(is_web_url("http://portal.example/login"));
+        assert!(is_web_url("HTTPS://portal.example"));
+        assert!(!is_web_url("file:///etc/passwd"));
+        assert!(!is_web_url("javascript:alert(1)"));
+        assert!(!is_web_url("/login"));
+    }
+
+    #[test]
+    fn splits_terse_output_on_unescaped_colons() {
+        assert_eq!(split_terse(r"yes:My\:Network:72"), ["yes", "My:Network", "72"]);
+    }
+
+    #[test]
+    fn unescapes_backslashes_in_terse_output() {
+        assert_eq!(split_terse(r"Back\\slash:WPA2"), [r"Back\slash", "WPA2"]);
+    }
+
+    #[test]
+    fn keeps_empty_terse_fields() {
+        assert_eq!(split_terse(":72::"), ["", "72", "", ""]);
+    }
+}
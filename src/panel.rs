@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use eframe::egui::{Ui, Vec2};
+
+/// A widget stacked in the window; the app drives every shown widget through this
+///
+/// Widgets are drawn in a frame filled with their palette's
+/// `surface_container_low`, padded by `margin` and rounded by `corner_radius`.
+pub trait Panel {
+    fn colors(&self) -> &super::Colors;
+
+    /// Size the widget needs right now, including the frame padding
+    fn size(&self) -> Vec2;
+
+    /// Smallest size the window may be shrunk to around the widget
+    fn min_size(&self) -> Vec2 {
+        self.size()
+    }
+
+    /// Largest size the widget can grow to, e.g. once expanded
+    fn max_size(&self) -> Vec2 {
+        self.size()
+    }
+
+    /// Padding between the panel's edge and its content
+    fn margin(&self) -> f32;
+
+    /// Corner radius of the panel unless --rounding overrides it
+    fn corner_radius(&self) -> u8 {
+        12
+    }
+
+    /// Time left until `should_update` turns true from the refresh interval
+    /// alone; None for widgets that never poll
+    fn next_update(&self) -> Option<Duration> {
+        None
+    }
+
+    fn should_update(&self) -> bool {
+        false
+    }
+
+    fn update(&mut self) {}
+
+    /// Draws the content inside the panel frame
+    fn show(&mut self, ui: &mut Ui);
+
+    /// Keys handled in `show`, paired with what they do
+    fn shortcuts(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Set once the widget is done, so the app closes the window
+    fn close_requested(&self) -> bool {
+        false
+    }
+
+    /// True while text is being typed into the widget, so Escape ends the edit
+    /// instead of closing the window
+    fn is_editing(&self) -> bool {
+        false
+    }
+
+    /// Whether the user may resize the window around the widget
+    fn resizable(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,106 @@
+use std::process::Command;
+
+use eframe::egui::{
+    Button,
+    RichText,
+    Ui,
+    Vec2,
+};
+
+use super::panel::Panel;
+
+const POWER_MARGIN: f32 = 8.0;
+/// Size of one square button
+const BUTTON_SIZE: f32 = 56.0;
+/// Gap between the buttons
+const BUTTON_SPACING: f32 = 8.0;
+const BUTTON_COUNT: usize = 5;
+const POWER_WIDTH: f32 = BUTTON_SIZE * BUTTON_COUNT as f32 + BUTTON_SPACING * (BUTTON_COUNT - 1) as f32 + 2.0 * POWER_MARGIN;
+const POWER_HEIGHT: f32 = BUTTON_SIZE + 2.0 * POWER_MARGIN;
+
+/// Options for the power menu
+#[derive(Debug, Clone)]
+pub struct PowerOptions {
+    /// Shell commands run by the buttons
+    pub lock_command: String,
+    pub logout_command: String,
+    pub suspend_command: String,
+    pub reboot_command: String,
+    pub shutdown_command: String,
+    /// Corner radius of the panel; buttons are capped at their default radius
+    pub rounding: Option<f32>,
+}
+
+/// Row of lock, logout, suspend, reboot and shutdown buttons
+pub struct PowerWidget {
+    colors: super::Colors,
+    options: PowerOptions,
+    /// Set once a command was started, so the window gets out of the way
+    close_requested: bool,
+}
+
+impl PowerWidget {
+    pub fn new(colors: super::Colors, options: PowerOptions) -> Self {
+        Self {
+            colors,
+            options,
+            close_requested: false,
+        }
+    }
+
+    /// Starts `command` through the shell without waiting for it
+    fn run(command: &str) {
+        if let Err(err) = super::spawn_reaped(Command::new("sh").args(["-c", command])) {
+            eprintln!("Warning: failed to run '{}': {}", command, err);
+        }
+    }
+}
+
+impl Panel for PowerWidget {
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(POWER_WIDTH, POWER_HEIGHT)
+    }
+
+    fn margin(&self) -> f32 {
+        POWER_MARGIN
+    }
+
+    fn close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        let buttons = [
+            (egui_phosphor::regular::LOCK, "Lock", &self.options.lock_command),
+            (egui_phosphor::regular::SIGN_OUT, "Log out", &self.options.logout_command),
+            (egui_phosphor::regular::MOON, "Suspend", &self.options.suspend_command),
+            (egui_phosphor::regular::ARROW_CLOCKWISE, "Reboot", &self.options.reboot_command),
+            (egui_phosphor::regular::POWER, "Shut down", &self.options.shutdown_command),
+        ];
+        let radius = super::corner_radius(self.options.rounding, 12).min(12);
+
+        let mut clicked = None;
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = BUTTON_SPACING;
+            for (icon, label, command) in buttons {
+                if ui.add(
+                    Button::new(RichText::new(icon).color(self.colors.primary_fixed_dim).size(26.0))
+                    .fill(self.colors.surface_container)
+                    .corner_radius(radius)
+                    .min_size(Vec2::splat(BUTTON_SIZE))
+                ).on_hover_text(label).clicked() {
+                    clicked = Some(command.clone());
+                }
+            }
+        });
+
+        if let Some(command) = clicked {
+            Self::run(&command);
+            self.close_requested = true;
+        }
+    }
+}
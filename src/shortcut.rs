@@ -0,0 +1,68 @@
+use eframe::egui::{InputState, Key};
+
+/// Keys a widget handles, written the way the help overlay lists them
+///
+/// Widgets act on the keys of the same table they list, so the overlay can't
+/// drift from what the keys actually do.
+pub struct Shortcut<A> {
+    /// Each key and what it does; empty for gestures such as scrolling
+    pub keys: Vec<(Key, A)>,
+    /// How the keys are written in the help overlay, e.g. `← / →`
+    pub label: String,
+    pub description: String,
+}
+
+impl<A: Copy> Shortcut<A> {
+    pub fn new(label: impl Into<String>, description: impl Into<String>, keys: Vec<(Key, A)>) -> Self {
+        Self {
+            keys,
+            label: label.into(),
+            description: description.into(),
+        }
+    }
+
+    /// Label and description for the help overlay
+    pub fn help(&self) -> (String, String) {
+        (self.label.clone(), self.description.clone())
+    }
+}
+
+/// Actions of the keys in `table` pressed this frame, in table order
+pub fn pressed<A: Copy>(input: &InputState, table: &[Shortcut<A>]) -> Vec<A> {
+    table.iter()
+        .flat_map(|shortcut| &shortcut.keys)
+        .filter(|(key, _)| input.key_pressed(*key))
+        .map(|(_, action)| *action)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::{Context, Event, RawInput};
+
+    /// Runs one frame with `keys` pressed and returns what `table` makes of them
+    fn press(keys: &[Key], table: &[Shortcut<u8>]) -> Vec<u8> {
+        let input = RawInput {
+            events: keys.iter()
+                .map(|&key| Event::Key { key, physical_key: None, pressed: true, repeat: false, modifiers: Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        let mut actions = Vec::new();
+        let _ = Context::default().run(input, |ctx| actions = ctx.input(|i| pressed(i, table)));
+        actions
+    }
+
+    #[test]
+    fn maps_pressed_keys_to_actions_in_table_order() {
+        let table = [
+            Shortcut::new("← / →", "Step", vec![(Key::ArrowLeft, 1), (Key::ArrowRight, 2)]),
+            Shortcut::new("Scroll", "Step", Vec::new()),
+            Shortcut::new("Tab", "Next", vec![(Key::Tab, 3)]),
+        ];
+        assert_eq!(press(&[Key::Tab, Key::ArrowLeft], &table), [1, 3]);
+        assert!(press(&[Key::Enter], &table).is_empty());
+        assert_eq!(table[1].help(), ("Scroll".to_string(), "Step".to_string()));
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+use eframe::egui::{
+    Align,
+    Layout,
+    RichText,
+    Sense,
+    Ui,
+    Vec2,
+};
+
+use super::panel::Panel;
+
+/// How often usage is sampled unless --refresh-ms says otherwise
+pub const DEFAULT_REFRESH: Duration = Duration::from_millis(1000);
+const SYSMON_MARGIN: f32 = 8.0;
+const SYSMON_WIDTH: f32 = 300.0;
+const SYSMON_HEIGHT: f32 = 80.0;
+/// Height of one labeled bar
+const ROW_HEIGHT: f32 = 28.0;
+/// Thickness of the usage bars
+const BAR_HEIGHT: f32 = 8.0;
+/// Room left of the bars for the icon and name, and right of them for the percentage
+const LABEL_WIDTH: f32 = 72.0;
+const VALUE_WIDTH: f32 = 44.0;
+
+/// Options for the system monitor widget
+#[derive(Debug, Clone)]
+pub struct SysMonOptions {
+    /// How often usage is sampled
+    pub refresh_interval: Duration,
+}
+
+/// Time the CPUs have spent since boot, in clock ticks
+#[derive(Debug, Clone, Copy)]
+struct CpuSample {
+    idle: u64,
+    total: u64,
+}
+
+impl CpuSample {
+    /// Reads the combined `cpu` line of /proc/stat
+    fn read() -> Option<Self> {
+        let stat = fs::read_to_string("/proc/stat").ok()?;
+        let times: Vec<u64> = stat
+            .lines()
+            .next()?
+            .strip_prefix("cpu ")?
+            .split_whitespace()
+            .filter_map(|value| value.parse().ok())
+            .collect();
+        if times.len() < 4 {
+            return None;
+        }
+        // user nice system idle iowait irq softirq steal; guest time is already part of user
+        Some(Self {
+            idle: times[3] + times.get(4).copied().unwrap_or(0),
+            total: times.iter().take(8).sum(),
+        })
+    }
+
+    /// Busy fraction of the time that passed since `previous`
+    fn usage_since(&self, previous: &CpuSample) -> Option<f32> {
+        let total = self.total.saturating_sub(previous.total);
+        if total == 0 {
+            return None;
+        }
+        let idle = self.idle.saturating_sub(previous.idle).min(total);
+        Some((total - idle) as f32 / total as f32)
+    }
+}
+
+/// Used and total memory in KiB, from /proc/meminfo
+fn read_memory() -> Option<(u64, u64)> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let value = |key: &str| {
+        meminfo.lines().find_map(|line| {
+            line.strip_prefix(key)?.strip_prefix(':')?.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    let total = value("MemTotal")?;
+    let available = value("MemAvailable")?;
+    Some((total.saturating_sub(available), total))
+}
+
+/// Formats KiB like `3.2 GiB`
+fn format_memory(kib: u64) -> String {
+    format!("{:.1} GiB", kib as f64 / (1024.0 * 1024.0))
+}
+
+/// CPU and memory usage bars
+pub struct SysMonWidget {
+    colors: super::Colors,
+    options: SysMonOptions,
+    /// Previous /proc/stat reading; usage is the difference to the next one
+    cpu_sample: Option<CpuSample>,
+    /// CPU usage as a fraction, once two samples have been taken
+    cpu: Option<f32>,
+    /// Used and total memory in KiB
+    memory: Option<(u64, u64)>,
+    last_update: Instant,
+}
+
+impl SysMonWidget {
+    pub fn new(colors: super::Colors, options: SysMonOptions) -> Self {
+        let mut widget = Self {
+            colors,
+            options,
+            cpu_sample: None,
+            cpu: None,
+            memory: None,
+            last_update: Instant::now(),
+        };
+        widget.update();
+        widget
+    }
+
+    /// One row: icon and name, a bar filled to `fraction`, and the percentage
+    fn show_bar(&self, ui: &mut Ui, icon: &str, name: &str, fraction: Option<f32>) -> eframe::egui::Response {
+        ui.horizontal(|ui| {
+            ui.set_height(ROW_HEIGHT);
+            ui.allocate_ui_with_layout(Vec2::new(LABEL_WIDTH, ROW_HEIGHT), Layout::left_to_right(Align::Center), |ui| {
+                ui.label(RichText::new(icon).color(self.colors.primary_fixed_dim).size(18.0));
+                ui.label(RichText::new(name).color(self.colors.on_surface_variant).size(14.0));
+            });
+
+            let width = ui.available_width() - VALUE_WIDTH;
+            let (rect, _) = ui.allocate_exact_size(Vec2::new(width, BAR_HEIGHT), Sense::hover());
+            let radius = BAR_HEIGHT / 2.0;
+            ui.painter().rect_filled(rect, radius, self.colors.surface_container);
+            if let Some(fraction) = fraction {
+                let mut filled = rect;
+                filled.set_width(rect.width() * fraction.clamp(0.0, 1.0));
+                ui.painter().rect_filled(filled, radius, self.colors.primary_fixed_dim);
+            }
+
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                let value = fraction.map_or("–".to_string(), |fraction| format!("{:.0}%", fraction * 100.0));
+                ui.label(RichText::new(value).color(self.colors.on_surface_variant).size(14.0));
+            });
+        })
+        .response
+    }
+}
+
+impl Panel for SysMonWidget {
+    fn next_update(&self) -> Option<Duration> {
+        Some(self.options.refresh_interval.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        self.last_update.elapsed() > self.options.refresh_interval
+    }
+
+    fn update(&mut self) {
+        let sample = CpuSample::read();
+        self.cpu = match (&sample, &self.cpu_sample) {
+            (Some(sample), Some(previous)) => sample.usage_since(previous),
+            _ => None,
+        };
+        self.cpu_sample = sample;
+        self.memory = read_memory();
+        self.last_update = Instant::now();
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(SYSMON_WIDTH, SYSMON_HEIGHT)
+    }
+
+    fn margin(&self) -> f32 {
+        SYSMON_MARGIN
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        ui.vertical(|ui| {
+            ui.set_width(SYSMON_WIDTH - 2.0 * SYSMON_MARGIN);
+            ui.set_height(SYSMON_HEIGHT - 2.0 * SYSMON_MARGIN);
+
+            self.show_bar(ui, egui_phosphor::regular::CPU, "CPU", self.cpu);
+
+            let fraction = self.memory
+                .filter(|(_, total)| *total > 0)
+                .map(|(used, total)| used as f32 / total as f32);
+            let response = self.show_bar(ui, egui_phosphor::regular::MEMORY, "RAM", fraction);
+            if let Some((used, total)) = self.memory {
+                response.on_hover_text(format!("{} of {} used", format_memory(used), format_memory(total)));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_is_the_busy_share_of_elapsed_time() {
+        let previous = CpuSample { idle: 800, total: 1000 };
+        assert_eq!(CpuSample { idle: 875, total: 1100 }.usage_since(&previous), Some(0.25));
+        assert_eq!(CpuSample { idle: 900, total: 1100 }.usage_since(&previous), Some(0.0));
+        assert_eq!(CpuSample { idle: 800, total: 1100 }.usage_since(&previous), Some(1.0));
+    }
+
+    #[test]
+    fn usage_needs_time_to_pass() {
+        let sample = CpuSample { idle: 800, total: 1000 };
+        assert_eq!(sample.usage_since(&sample), None);
+        // Counters going backwards, e.g. a CPU going offline, don't underflow
+        assert_eq!(CpuSample { idle: 700, total: 900 }.usage_since(&sample), None);
+        assert_eq!(CpuSample { idle: 500, total: 1100 }.usage_since(&sample), Some(1.0));
+    }
+}
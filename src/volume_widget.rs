@@ -0,0 +1,290 @@
+use std::{
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui::{
+    Button,
+    Color32,
+    Context,
+    RichText,
+    Slider,
+    Ui,
+    Vec2,
+};
+
+use super::panel::Panel;
+
+/// wpctl's name for the default output
+const DEFAULT_SINK: &str = "@DEFAULT_AUDIO_SINK@";
+/// How often the volume is read unless --refresh-ms says otherwise
+pub const DEFAULT_REFRESH: Duration = Duration::from_millis(1000);
+const VOLUME_MARGIN: f32 = 8.0;
+const VOLUME_WIDTH: f32 = 320.0;
+const VOLUME_HEIGHT: f32 = 48.0;
+
+/// Options for the volume widget
+#[derive(Debug, Clone)]
+pub struct VolumeOptions {
+    /// How often the volume is read
+    pub refresh_interval: Duration,
+}
+
+/// Level and mute state of the default output
+#[derive(Debug, Clone, Copy)]
+struct Volume {
+    /// 1.0 is 100%; wpctl allows boosting past that
+    level: f32,
+    muted: bool,
+}
+
+impl Volume {
+    /// Parses wpctl's `Volume: 0.45` or `Volume: 0.45 [MUTED]`
+    fn parse(output: &str) -> Option<Self> {
+        let mut words = output.strip_prefix("Volume:")?.split_whitespace();
+        let level = words.next()?.parse().ok()?;
+        Some(Self { level, muted: words.any(|word| word == "[MUTED]") })
+    }
+
+    fn read() -> Option<Self> {
+        let output = Command::new("wpctl").args(["get-volume", DEFAULT_SINK]).output().ok()?;
+        Self::parse(&String::from_utf8(output.stdout).ok()?)
+    }
+}
+
+/// Work for the thread that runs wpctl
+#[derive(Debug, Clone, Copy)]
+enum VolumeRequest {
+    Read,
+    Level(f32),
+    ToggleMute,
+}
+
+/// Volume read back by the worker
+struct Reading {
+    volume: Option<Volume>,
+    /// Changes the worker had received when it read the volume
+    changes: u32,
+}
+
+/// Runs wpctl off the UI thread, one batch of requests at a time and in order
+///
+/// Only the latest level of a batch is set, so the last slider position wins, and
+/// every batch ends with a reading that `update` picks up.
+fn spawn_worker(readings: Sender<Reading>, ready: Arc<AtomicBool>, repaint_ctx: Arc<OnceLock<Context>>) -> Sender<VolumeRequest> {
+    let (sender, receiver) = mpsc::channel::<VolumeRequest>();
+    thread::spawn(move || {
+        let mut changes = 0;
+        while let Ok(request) = receiver.recv() {
+            let mut requests = vec![request];
+            requests.extend(receiver.try_iter());
+            let last_level = requests.iter().rposition(|request| matches!(request, VolumeRequest::Level(_)));
+
+            for (index, request) in requests.into_iter().enumerate() {
+                let args = match request {
+                    VolumeRequest::Read => continue,
+                    VolumeRequest::Level(_) if Some(index) != last_level => {
+                        changes += 1;
+                        continue;
+                    }
+                    VolumeRequest::Level(level) => vec!["set-volume".to_string(), DEFAULT_SINK.to_string(), format!("{:.2}", level)],
+                    VolumeRequest::ToggleMute => vec!["set-mute".to_string(), DEFAULT_SINK.to_string(), "toggle".to_string()],
+                };
+                Command::new("wpctl").args(&args).stdout(Stdio::null()).status().ok();
+                changes += 1;
+            }
+
+            if readings.send(Reading { volume: Volume::read(), changes }).is_err() {
+                break;
+            }
+            ready.store(true, Ordering::Release);
+            if let Some(ctx) = repaint_ctx.get() {
+                ctx.request_repaint();
+            }
+        }
+    });
+    sender
+}
+
+/// Slider for the default output's volume
+pub struct VolumeWidget {
+    colors: super::Colors,
+    options: VolumeOptions,
+    /// None when wpctl isn't available or there is no output
+    volume: Option<Volume>,
+    /// False until the first reading has come back
+    loaded: bool,
+    /// Polling waits while the slider is held so it doesn't jump back
+    dragging: bool,
+    /// Worker running wpctl; it exits once the widget is dropped
+    requests: Sender<VolumeRequest>,
+    readings: Receiver<Reading>,
+    /// Set by the worker once a reading is waiting in the channel
+    refresh_ready: Arc<AtomicBool>,
+    /// Context the worker wakes; known once the widget has been shown
+    repaint_ctx: Arc<OnceLock<Context>>,
+    /// Changes sent to the worker; readings taken before the last of them are stale
+    changes: u32,
+    last_update: Instant,
+}
+
+impl VolumeWidget {
+    pub fn new(colors: super::Colors, options: VolumeOptions) -> Self {
+        let (reading_sender, readings) = mpsc::channel();
+        let refresh_ready = Arc::new(AtomicBool::new(false));
+        let repaint_ctx = Arc::new(OnceLock::new());
+        let requests = spawn_worker(reading_sender, refresh_ready.clone(), repaint_ctx.clone());
+        requests.send(VolumeRequest::Read).ok();
+        Self {
+            colors,
+            options,
+            volume: None,
+            loaded: false,
+            dragging: false,
+            requests,
+            readings,
+            refresh_ready,
+            repaint_ctx,
+            changes: 0,
+            last_update: Instant::now(),
+        }
+    }
+
+    fn get_speaker_icon(volume: &Volume) -> &'static str {
+        if volume.muted {
+            egui_phosphor::regular::SPEAKER_X
+        } else if volume.level <= 0.0 {
+            egui_phosphor::regular::SPEAKER_NONE
+        } else if volume.level < 0.5 {
+            egui_phosphor::regular::SPEAKER_LOW
+        } else {
+            egui_phosphor::regular::SPEAKER_HIGH
+        }
+    }
+}
+
+impl Panel for VolumeWidget {
+    fn next_update(&self) -> Option<Duration> {
+        Some(self.options.refresh_interval.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        (!self.dragging && self.last_update.elapsed() > self.options.refresh_interval)
+            || self.refresh_ready.load(Ordering::Acquire)
+    }
+
+    /// Applies readings from the worker, and asks for the next one when it's due
+    fn update(&mut self) {
+        self.refresh_ready.store(false, Ordering::Release);
+        while let Ok(reading) = self.readings.try_recv() {
+            // Taken before the latest change was applied; it would snap the slider back
+            if reading.changes < self.changes {
+                continue;
+            }
+            self.loaded = true;
+            if !self.dragging {
+                self.volume = reading.volume;
+            }
+        }
+
+        if !self.dragging && self.last_update.elapsed() > self.options.refresh_interval {
+            self.requests.send(VolumeRequest::Read).ok();
+            self.last_update = Instant::now();
+        }
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(VOLUME_WIDTH, VOLUME_HEIGHT)
+    }
+
+    fn margin(&self) -> f32 {
+        VOLUME_MARGIN
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        // Readings finishing later need a context to wake
+        self.repaint_ctx.get_or_init(|| ui.ctx().clone());
+
+        ui.horizontal(|ui| {
+            ui.set_width(VOLUME_WIDTH - 2.0 * VOLUME_MARGIN);
+            ui.set_height(VOLUME_HEIGHT - 2.0 * VOLUME_MARGIN);
+
+            let Some(volume) = &mut self.volume else {
+                ui.label(RichText::new(egui_phosphor::regular::SPEAKER_SLASH).color(self.colors.outline).size(22.0));
+                if self.loaded {
+                    ui.label(RichText::new("No audio output").color(self.colors.on_surface_variant).size(16.0));
+                }
+                return;
+            };
+
+            let color = if volume.muted { self.colors.outline } else { self.colors.primary_fixed_dim };
+            if ui.add(
+                Button::new(RichText::new(Self::get_speaker_icon(volume)).color(color).size(22.0))
+                .fill(Color32::TRANSPARENT)
+                .frame(false)
+            ).on_hover_text(if volume.muted { "Unmute" } else { "Mute" }).clicked() {
+                volume.muted = !volume.muted;
+                self.changes += 1;
+                self.requests.send(VolumeRequest::ToggleMute).ok();
+            }
+
+            // Boosted levels past 100% show as a full slider
+            let mut percent = (volume.level * 100.0).round().min(100.0);
+            let label = format!("{}%", (volume.level * 100.0).round());
+            ui.scope(|ui| {
+                super::style_slider(ui, &self.colors);
+                ui.spacing_mut().slider_width = ui.available_width() - 48.0;
+                let response = ui.add(Slider::new(&mut percent, 0.0..=100.0).show_value(false).trailing_fill(true));
+                self.dragging = response.dragged();
+                if response.changed() {
+                    volume.level = percent / 100.0;
+                    self.changes += 1;
+                    self.requests.send(VolumeRequest::Level(volume.level)).ok();
+                }
+            });
+            ui.label(RichText::new(label).color(self.colors.on_surface_variant).size(14.0));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_volume_level() {
+        let volume = Volume::parse("Volume: 0.45").unwrap();
+        assert_eq!(volume.level, 0.45);
+        assert!(!volume.muted);
+    }
+
+    #[test]
+    fn parses_muted_suffix() {
+        let volume = Volume::parse("Volume: 0.45 [MUTED]\n").unwrap();
+        assert_eq!(volume.level, 0.45);
+        assert!(volume.muted);
+    }
+
+    #[test]
+    fn parses_boosted_level() {
+        assert_eq!(Volume::parse("Volume: 1.30").unwrap().level, 1.3);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(Volume::parse("").is_none());
+        assert!(Volume::parse("Translate endpoint failed").is_none());
+        assert!(Volume::parse("Volume: loud").is_none());
+    }
+}
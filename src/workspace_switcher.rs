@@ -5,42 +5,88 @@ use std::{
     collections::HashMap,
     path::Path,
     cell::RefCell,
+    sync::Once,
 };
 
 use eframe::egui::{
-
     Align2,
     Button,
     Color32,
     FontFamily,
     FontId,
+    CursorIcon,
+    Id,
     Image,
     Key,
-    Rounding,
+    LayerId,
+    Order,
     Sense,
+    CornerRadius,
     TextureHandle,
     Ui,
     Vec2,
     Rect,
     Pos2,
-    ViewportCommand,
+    Stroke,
+    StrokeKind,
+    TextEdit,
 };
 
+use log::warn;
 use serde::{Deserialize, Serialize};
 use resvg::usvg;
 use tiny_skia::Pixmap;
-use shellexpand;
-
-/// Path to the colors configuration file
-const COLORS_CONFIG_PATH: &str = "~/.config/hypr/hyprland/colors.conf";
-/// Default icon size used throughout the application
 
+use super::hyprctl;
+use super::panel::Panel;
+use super::shortcut::{self, Shortcut};
+
+/// Path to the user's window class to icon overrides
+pub const ICON_OVERRIDES_PATH: &str = "~/.config/hypowertools/icon-overrides.toml";
+/// Longest edge, in pixels, of the background texture uploaded for workspace buttons
+const BACKGROUND_MAX_SIZE: u32 = 512;
+/// Duration of the highlight pulse after switching workspaces, in seconds
+const SWITCH_PULSE_SECS: f64 = 0.35;
+/// Width of a monitor row in overview mode
+const OVERVIEW_ROW_WIDTH: f32 = 240.0;
+/// Height of a monitor row in overview mode
+const OVERVIEW_ROW_HEIGHT: f32 = 36.0;
+/// Vertical gap between monitor rows in overview mode
+const OVERVIEW_ROW_SPACING: f32 = 6.0;
+/// Scroll events within this long after a switch are ignored, so one notch moves one workspace
+const SCROLL_DEBOUNCE: Duration = Duration::from_millis(150);
+/// Size of the icon following the pointer while a window is dragged
+const DRAG_ICON_SIZE: f32 = 32.0;
+/// Width of a special workspace button; they're as tall as the others
+const SPECIAL_BUTTON_WIDTH: f32 = 72.0;
+/// Gap between the regular workspaces and the special ones after them
+const SPECIAL_GROUP_GAP: f32 = 12.0;
+/// Room kept after the last app icon for the `+N` of windows that didn't fit
+const OVERFLOW_LABEL_WIDTH: f32 = 24.0;
 
 /// Represents a Hyprland workspace
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 struct Workspace {
     id: i32,
     name: String,
+    /// Name of the monitor the workspace lives on
+    #[serde(default)]
+    monitor: String,
+    /// Id of that monitor; missing from `activeworkspace` on older Hyprland versions
+    #[serde(default, rename = "monitorID")]
+    monitor_id: Option<i32>,
+}
+
+impl Workspace {
+    /// Scratchpads like `special:magic`, which Hyprland gives negative ids
+    fn is_special(&self) -> bool {
+        self.id < 0 || self.name.starts_with("special:")
+    }
+
+    /// Name `togglespecialworkspace` expects, without the `special:` prefix
+    fn special_name(&self) -> &str {
+        self.name.strip_prefix("special:").unwrap_or(&self.name)
+    }
 }
 
 /// Represents a window in Hyprland with its properties
@@ -111,42 +157,210 @@ struct WorkspaceInfo {
 
 /// Information about a monitor
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct Monitor {
-    id: i32,
-    name: String,
-    x: i32,
-    y: i32,
-    width: i32,
-    height: i32,
+pub struct Monitor {
+    pub id: i32,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub focused: bool,
+    /// wl_output transform; odd values are rotated by 90 or 270 degrees
+    #[serde(default)]
+    pub transform: i32,
     #[serde(rename = "activeWorkspace")]
     active_workspace: WorkspaceInfo,
 }
 
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl Monitor {
+    /// Size in layout coordinates, as used by `movewindowpixel`
+    ///
+    /// `width` and `height` are the mode's pixel size, so rotated outputs swap them.
+    pub fn logical_size(&self) -> (i32, i32) {
+        let (width, height) = if self.transform % 2 == 1 {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+        (
+            (width as f32 / self.scale).round() as i32,
+            (height as f32 / self.scale).round() as i32,
+        )
+    }
+
+    /// Id of the workspace currently shown on this monitor
+    pub fn active_workspace_id(&self) -> i32 {
+        self.active_workspace.id
+    }
+
+    /// Whether `spec` names this monitor, by connector name (e.g. "DP-1") or numeric id
+    fn matches(&self, spec: &str) -> bool {
+        self.name == spec || spec.parse::<i32>().is_ok_and(|id| id == self.id)
+    }
+
+    /// Whether `workspace` lives on this monitor
+    fn holds(&self, workspace: &Workspace) -> bool {
+        workspace.monitor_id.map_or(workspace.monitor == self.name, |id| id == self.id)
+    }
+}
+
+/// Guards the warning printed when hyprctl monitors gives nothing usable; the
+/// monitors are polled, so it would otherwise repeat on every refresh
+static MONITORS_WARNING: Once = Once::new();
+
+/// Lists all monitors known to Hyprland
+pub fn get_monitors() -> Vec<Monitor> {
+    let Some(output) = hyprctl::run(["monitors", "-j"]) else {
+        return Vec::new();
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match serde_json::from_str::<Vec<Monitor>>(&stdout) {
+        Ok(monitors) => {
+            if monitors.is_empty() {
+                MONITORS_WARNING.call_once(|| warn!("hyprctl monitors returned no monitors"));
+            }
+            monitors
+        }
+        Err(err) => {
+            MONITORS_WARNING.call_once(|| warn!("Failed to parse hyprctl monitors output: {}", err));
+            Vec::new()
+        }
+    }
+}
+
+/// Finds a monitor by connector name (e.g. "DP-1") or numeric id
+pub fn find_monitor(spec: &str) -> Option<Monitor> {
+    get_monitors().into_iter().find(|m| m.matches(spec))
+}
+
+/// Returns the focused monitor, falling back to the one showing the active workspace
+pub fn get_active_monitor() -> Option<Monitor> {
+    let monitors = get_monitors();
+    if let Some(focused) = monitors.iter().find(|m| m.focused) {
+        return Some(focused.clone());
+    }
+
+    let active_name = hyprctl::run(["activeworkspace", "-j"])
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .and_then(|stdout| serde_json::from_str::<Workspace>(&stdout).ok())
+        .map(|workspace| workspace.monitor);
+
+    active_name
+        .and_then(|name| monitors.iter().find(|m| m.name == name).cloned())
+        .or_else(|| monitors.into_iter().next())
+}
+
+/// How often workspaces are polled unless --refresh-ms says otherwise
+pub const DEFAULT_REFRESH: Duration = Duration::from_millis(500);
+/// Default corner radius of the workspace buttons
+const BUTTON_RADIUS: u8 = 15;
+/// Gap between a button's edge and its background image
+const BACKGROUND_INSET: u8 = 2;
+
+/// How long a failed icon lookup is remembered before retrying
+const NEGATIVE_CACHE_TTL: Duration = Duration::from_secs(60);
+
 /// Cache for storing loaded application icons
+///
+/// Misses are kept with the time of the lookup so they can be retried once
+/// `NEGATIVE_CACHE_TTL` has passed, e.g. after an icon theme is installed.
 struct IconCache {
-    cache: RefCell<HashMap<String, Option<TextureHandle>>>,
+    cache: RefCell<HashMap<String, (Option<TextureHandle>, Instant)>>,
+    /// Window class to icon name or absolute icon path
+    overrides: HashMap<String, String>,
 }
 
 impl IconCache {
     fn new() -> Self {
         Self {
             cache: RefCell::new(HashMap::new()),
+            overrides: Self::load_overrides(),
         }
     }
 
-    fn get_or_load(&self, ui: &mut Ui, class_name: &str) -> Option<TextureHandle> {
-        if let Some(cached_icon) = self.cache.borrow().get(class_name) {
-            return cached_icon.clone();
+    /// Built-in class overrides merged with the user's icon-overrides.toml
+    fn load_overrides() -> HashMap<String, String> {
+        let mut overrides: HashMap<String, String> = [
+            ("Cursor", "com.cursor.Cursor"),
+            // Handle both native and Flatpak Discord
+            ("discord", "com.discordapp.Discord"),
+            ("Discord", "com.discordapp.Discord"),
+        ]
+        .into_iter()
+        .map(|(class, icon)| (class.to_string(), icon.to_string()))
+        .collect();
+
+        let path = shellexpand::tilde(ICON_OVERRIDES_PATH).to_string();
+        if let Ok(content) = fs::read_to_string(&path) {
+            match toml::from_str::<HashMap<String, String>>(&content) {
+                Ok(user) => overrides.extend(user),
+                Err(err) => eprintln!("Warning: ignoring invalid {}: {}", path, err),
+            }
         }
+        overrides
+    }
 
-        // Special case mappings for known apps
-        let lookup_class = match class_name {
-            "Cursor" => "com.cursor.Cursor",
-            "discord" => "com.discordapp.Discord",
-            // Handle both native and Flatpak Discord
-            "Discord" => "com.discordapp.Discord",
-            _ => class_name
-        };
+    fn get_or_load(&self, ui: &mut Ui, class_name: &str, pid: i32) -> Option<TextureHandle> {
+        if let Some(icon) = self.lookup(ui, class_name) {
+            return Some(icon);
+        }
+
+        // Generic window classes (e.g. Electron) often match the executable instead
+        let exe_name = Self::executable_name(pid)?;
+        if exe_name.eq_ignore_ascii_case(class_name) {
+            return None;
+        }
+        self.lookup(ui, &exe_name)
+    }
+
+    /// Reads the executable name of a process from /proc
+    fn executable_name(pid: i32) -> Option<String> {
+        if pid <= 0 {
+            return None;
+        }
+
+        fs::read_link(format!("/proc/{}/exe", pid))
+            .ok()
+            .and_then(|exe| exe.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .or_else(|| fs::read_to_string(format!("/proc/{}/comm", pid)).ok().map(|comm| comm.trim().to_string()))
+            .filter(|name| !name.is_empty())
+    }
+
+    fn lookup(&self, ui: &mut Ui, name: &str) -> Option<TextureHandle> {
+        if let Some((cached_icon, looked_up)) = self.cache.borrow().get(name) {
+            if cached_icon.is_some() || looked_up.elapsed() < NEGATIVE_CACHE_TTL {
+                return cached_icon.clone();
+            }
+        }
+
+        let icon = self.find_icon_path(name).and_then(|path| {
+            if path.ends_with(".svg") {
+                self.load_svg_or_fallback(&path, ui)
+            } else {
+                self.load_png(&path, ui)
+            }
+        });
+
+        self.cache.borrow_mut().insert(name.to_string(), (icon.clone(), Instant::now()));
+        icon
+    }
+
+    fn find_icon_path(&self, class_name: &str) -> Option<String> {
+        // User and built-in overrides map a class to an icon name or an icon file
+        let lookup_class = self.overrides.get(class_name).map_or(class_name, String::as_str);
+        if lookup_class.starts_with('/') || lookup_class.starts_with('~') {
+            let expanded_path = shellexpand::tilde(lookup_class).to_string();
+            if Path::new(&expanded_path).exists() {
+                return Some(expanded_path);
+            }
+        }
 
         // Additional Flatpak-specific paths for Discord
         if lookup_class == "com.discordapp.Discord" {
@@ -159,7 +373,7 @@ impl IconCache {
             for path in &flatpak_paths {
                 let expanded_path = shellexpand::tilde(path).to_string();
                 if Path::new(&expanded_path).exists() {
-                    return self.load_png(&expanded_path, ui);
+                    return Some(expanded_path);
                 }
             }
         }
@@ -182,7 +396,7 @@ impl IconCache {
         let mut found_icon_name = None;
 
         // First pass: try to find exact class match in desktop files
-        'desktop_search: for path in desktop_files.lines() {
+        for path in desktop_files.lines() {
             let expanded_path = shellexpand::tilde(path).to_string();
             if let Ok(content) = fs::read_to_string(&expanded_path) {
                 // Check if this desktop file is for our app
@@ -274,18 +488,21 @@ impl IconCache {
             }
         }
 
-        let icon = if let Some(path) = icon_path {
-            if path.ends_with(".svg") {
-                self.load_svg(&path, ui)
-            } else {
-                self.load_png(&path, ui)
-            }
-        } else {
-            None
-        };
+        icon_path
+    }
 
-        self.cache.borrow_mut().insert(class_name.to_string(), icon.clone());
-        icon
+    /// Loads an SVG, falling back to a PNG next to it when usvg can't render it
+    fn load_svg_or_fallback(&self, path: &str, ui: &mut Ui) -> Option<TextureHandle> {
+        if let Some(icon) = self.load_svg(path, ui) {
+            return Some(icon);
+        }
+
+        log::debug!("Failed to render SVG icon {}", path);
+        let png_path = Path::new(path).with_extension("png");
+        if png_path.exists() {
+            return self.load_png(&png_path.to_string_lossy(), ui);
+        }
+        None
     }
 
     fn load_svg(&self, path: &str, ui: &mut Ui) -> Option<TextureHandle> {
@@ -336,35 +553,145 @@ impl IconCache {
     }
 }
 
+/// When switching workspaces closes the switcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClosePolicy {
+    /// Number keys switch and close; clicks and arrows keep it open
+    NumberKeys,
+    /// Never close after switching
+    KeepOpen,
+    /// Close after any switch
+    Once,
+}
+
+/// Options for the workspace switcher
+#[derive(Debug, Clone)]
+pub struct SwitcherOptions {
+    /// Command launched on a workspace when its button is middle-clicked
+    pub middle_click_command: String,
+    /// Label workspaces 1..n by position instead of their real names
+    pub remap_indices: bool,
+    /// Inset of the icon strip from the button's top-left corner
+    pub icon_margin: f32,
+    /// Inset of the workspace label from the button's bottom-left corner
+    pub label_margin: f32,
+    /// Show one row per monitor with its active workspace instead of the workspace strip
+    pub overview: bool,
+    /// Workspace to pre-highlight so Enter switches to it
+    pub select: Option<i32>,
+    /// Dispatcher (with arguments) that hands off to a full workspace overview
+    pub overview_command: String,
+    /// Key that triggers `overview_command`
+    pub overview_key: Key,
+    /// Strength of the black dimming layer over workspace backgrounds (0.0-1.0)
+    pub bg_dim: f32,
+    /// Strength of the surface-color tint over workspace backgrounds (0.0-1.0)
+    pub bg_tint: f32,
+    /// Skip decorative animations
+    pub reduce_motion: bool,
+    /// Paint the wallpaper behind each workspace button
+    pub background: bool,
+    /// Badge icons of windows running through XWayland
+    pub mark_xwayland: bool,
+    /// Colors config whose `$image` entry is the button wallpaper
+    pub colors_config: String,
+    /// Whether switching workspaces closes the switcher
+    pub close_policy: ClosePolicy,
+    /// How often workspaces and windows are polled
+    pub refresh_interval: Duration,
+    /// Corner radius of the workspace buttons, overriding `BUTTON_RADIUS`
+    pub rounding: Option<f32>,
+    /// Shortest time between frames of the switch pulse, from --max-fps
+    pub frame_interval: Option<Duration>,
+    /// Only list the workspaces of the monitor the switcher is shown on
+    pub monitor_workspaces: bool,
+    /// Monitor given with --monitor, by name or id; the focused one otherwise
+    pub monitor: Option<String>,
+    /// Leave out workspaces without windows, except the current one
+    pub hide_empty: bool,
+    /// App icons drawn per workspace button before the rest are summed up as +N
+    pub max_icons: usize,
+}
+
+/// What the switcher's keys do
+#[derive(Debug, Clone, Copy)]
+enum SwitcherKey {
+    Previous,
+    Next,
+    /// Workspace number, or position with --remap-indices, 1 to 10
+    Workspace(i32),
+    /// Switch to the highlighted workspace, if any, and close
+    Confirm,
+    Rename,
+    Overview,
+    Close,
+}
+
+/// Number keys in workspace order; 0 stands for the tenth
+const NUMBER_KEYS: [Key; 10] = [
+    Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5,
+    Key::Num6, Key::Num7, Key::Num8, Key::Num9, Key::Num0,
+];
+
 /// Main workspace switcher widget
 pub struct WorkspaceSwitcher {
     colors: super::Colors,
+    options: SwitcherOptions,
     current_workspace: i32,
     workspaces: Vec<Workspace>,
+    /// Clients as of the last update, shared by sizing and drawing
+    windows: Vec<Window>,
+    monitors: Vec<Monitor>,
+    selected_workspace: Option<i32>,
+    overview_available: bool,
+    /// Workspace switched to through the widget and the input time it happened
+    switch_pulse: Option<(i32, f64)>,
+    /// Workspace being renamed and the name typed so far
+    renaming: Option<(i32, String)>,
+    /// Set once the switcher is done; the app closes the window
+    close_requested: bool,
+    /// Size taken up when last drawn, including the frame padding
+    shown_size: Option<Vec2>,
+    /// When scrolling last switched workspaces
+    last_scroll_switch: Option<Instant>,
     last_update: Instant,
     background: Option<TextureHandle>,
     icon_cache: IconCache,
-    selected_window: Option<String>,
 }
 
 impl WorkspaceSwitcher {
-    pub fn new(colors: super::Colors) -> Self {
+    pub fn new(colors: super::Colors, options: SwitcherOptions) -> Self {
         let mut switcher = Self {
             colors,
+            options,
             current_workspace: 1,
             workspaces: Vec::new(),
+            windows: Vec::new(),
+            monitors: Vec::new(),
+            selected_workspace: None,
+            overview_available: true,
+            switch_pulse: None,
+            renaming: None,
+            close_requested: false,
+            shown_size: None,
+            last_scroll_switch: None,
             last_update: Instant::now(),
             background: None,
             icon_cache: IconCache::new(),
-            selected_window: None,
         };
         
         switcher.update();
+
+        // Pre-highlight the requested workspace, or the current one if it doesn't exist
+        if let Some(id) = switcher.options.select {
+            let exists = switcher.workspaces.iter().any(|w| w.id == id);
+            switcher.selected_workspace = Some(if exists { id } else { switcher.current_workspace });
+        }
         switcher
     }
 
-    fn get_background_path() -> Option<String> {
-        let config_path = shellexpand::tilde(COLORS_CONFIG_PATH).to_string();
+    fn get_background_path(&self) -> Option<String> {
+        let config_path = shellexpand::tilde(&self.options.colors_config).to_string();
         if let Ok(content) = fs::read_to_string(config_path) {
             for line in content.lines() {
                 if let Some((key, value)) = line.split_once('=') {
@@ -380,7 +707,7 @@ impl WorkspaceSwitcher {
     }
 
     fn get_workspaces() -> Vec<Workspace> {
-        if let Ok(output) = Command::new("hyprctl").args(&["workspaces", "-j"]).output() {
+        if let Some(output) = hyprctl::run(["workspaces", "-j"]) {
             if let Ok(stdout) = String::from_utf8(output.stdout) {
                 if let Ok(mut workspaces) = serde_json::from_str::<Vec<Workspace>>(&stdout) {
                     workspaces.sort_by_key(|w| w.id);
@@ -392,7 +719,7 @@ impl WorkspaceSwitcher {
     }
 
     fn get_current_workspace() -> i32 {
-        if let Ok(output) = Command::new("hyprctl").args(&["activeworkspace", "-j"]).output() {
+        if let Some(output) = hyprctl::run(["activeworkspace", "-j"]) {
             if let Ok(stdout) = String::from_utf8(output.stdout) {
                 if let Ok(workspace) = serde_json::from_str::<Workspace>(&stdout) {
                     return workspace.id;
@@ -403,70 +730,283 @@ impl WorkspaceSwitcher {
     }
 
     fn get_windows() -> Vec<Window> {
-        let output = match Command::new("hyprctl")
-            .args(["clients", "-j"])
-            .output() {
-                Ok(output) => output,
-                Err(_) => return Vec::new(),
-            };
+        let output = match hyprctl::run(["clients", "-j"]) {
+            Some(output) => output,
+            None => return Vec::new(),
+        };
 
         let output_str = match String::from_utf8(output.stdout) {
             Ok(s) => s,
             Err(_) => return Vec::new(),
         };
 
-        match serde_json::from_str::<Vec<Window>>(&output_str) {
-            Ok(windows) => windows,
-            Err(_) => Vec::new(),
-        }
+        serde_json::from_str::<Vec<Window>>(&output_str).unwrap_or_default()
     }
 
 
     fn switch_to_workspace(&mut self, workspace_id: i32) {
         if let Some(workspace) = self.workspaces.iter().find(|w| w.id == workspace_id) {
             // First switch to the workspace
-            Command::new("hyprctl")
-                .args(&["dispatch", "workspace", &workspace.name])
-                .output()
-                .ok();
+            hyprctl::run(["dispatch", "workspace", &workspace.name]);
 
         }
     }
 
-    pub fn should_update(&self) -> bool {
-        self.last_update.elapsed() > Duration::from_millis(500)
+    /// Moves windows to `target` (an id or `special:name`) without following them
+    fn move_windows(&mut self, addresses: &[String], target: &str) {
+        for address in addresses {
+            hyprctl::run(["dispatch", "movetoworkspacesilent", &format!("{},address:{}", target, address)]);
+        }
+        self.update();
     }
 
-    pub fn update(&mut self) {
-        self.workspaces = Self::get_workspaces();
-        self.current_workspace = Self::get_current_workspace();
-        self.last_update = Instant::now();
+    fn toggle_special_workspace(&self, name: &str) {
+        hyprctl::run(["dispatch", "togglespecialworkspace", name]);
     }
 
-    pub fn colors(&self) -> &super::Colors {
-        &self.colors
+    fn launch_on_workspace(&self, workspace_id: i32) {
+        let rule = format!("[workspace {}] {}", workspace_id, self.options.middle_click_command);
+        hyprctl::run(["dispatch", "exec", &rule]);
+    }
+
+    /// Hands off to the configured overview, disabling the shortcut if the dispatch fails
+    fn open_overview(&mut self) -> bool {
+        let args: Vec<&str> = std::iter::once("dispatch")
+            .chain(self.options.overview_command.split_whitespace())
+            .collect();
+        let dispatched = hyprctl::run(&args)
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .is_some_and(|stdout| stdout.trim() == "ok");
+
+        if !dispatched {
+            eprintln!("Warning: '{}' failed, is the overview plugin loaded? Disabling the shortcut", self.options.overview_command);
+            self.overview_available = false;
+        }
+        dispatched
     }
 
-    pub fn workspaces(&self) -> &Vec<Workspace> {
-        &self.workspaces
+    /// Keys handled in `show`, with their help text
+    fn key_table(&self) -> Vec<Shortcut<SwitcherKey>> {
+        if self.options.overview {
+            return vec![Shortcut::new("Enter", "Close", vec![(Key::Enter, SwitcherKey::Close)])];
+        }
+
+        let number_description = match (self.options.remap_indices, self.options.close_policy) {
+            (true, ClosePolicy::KeepOpen) => "Switch to the nth workspace",
+            (true, _) => "Switch to the nth workspace and close",
+            (false, ClosePolicy::KeepOpen) => "Switch to workspace 1-10",
+            (false, _) => "Switch to workspace 1-10 and close",
+        };
+        let enter_description = if self.selected_workspace.is_some() {
+            "Switch to the highlighted workspace and close"
+        } else {
+            "Close"
+        };
+        let mut table = vec![
+            Shortcut::new(
+                "← / →",
+                "Previous / next workspace",
+                vec![(Key::ArrowLeft, SwitcherKey::Previous), (Key::ArrowRight, SwitcherKey::Next)],
+            ),
+            Shortcut::new("Tab", "Next workspace", vec![(Key::Tab, SwitcherKey::Next)]),
+            Shortcut::new("Scroll", "Previous / next workspace", Vec::new()),
+            Shortcut::new(
+                "1-9, 0",
+                number_description,
+                NUMBER_KEYS.into_iter().zip(1..).map(|(key, number)| (key, SwitcherKey::Workspace(number))).collect(),
+            ),
+            Shortcut::new("Enter", enter_description, vec![(Key::Enter, SwitcherKey::Confirm)]),
+            Shortcut::new("F2", "Rename the highlighted workspace", vec![(Key::F2, SwitcherKey::Rename)]),
+        ];
+        if self.overview_available {
+            table.push(Shortcut::new(
+                self.options.overview_key.name(),
+                "Open the full overview",
+                vec![(self.options.overview_key, SwitcherKey::Overview)],
+            ));
+        }
+        table
+    }
+
+    fn rename_workspace(&mut self, workspace_id: i32, name: &str) {
+        hyprctl::run(["dispatch", "renameworkspace", &workspace_id.to_string(), name]);
+        self.update();
+    }
+
+    /// Drops workspaces living on other monitors; the current workspace becomes
+    /// the one this monitor shows, even when another monitor has focus
+    fn filter_to_monitor(&mut self) {
+        let monitor = match &self.options.monitor {
+            Some(spec) => self.monitors.iter().find(|m| m.matches(spec)),
+            None => self.monitors.iter().find(|m| m.focused),
+        };
+        let Some(monitor) = monitor else {
+            return;
+        };
+        // Scratchpads follow whichever monitor opens them, so keep them all
+        self.workspaces.retain(|w| w.is_special() || monitor.holds(w));
+        self.current_workspace = monitor.active_workspace_id();
+    }
+
+    fn is_overview(&self) -> bool {
+        self.options.overview
+    }
+
+    /// Window size needed for the overview rows, including the frame padding
+    fn overview_size(&self) -> Vec2 {
+        let rows = self.monitors.len().max(1) as f32;
+        Vec2::new(
+            OVERVIEW_ROW_WIDTH + 12.0,
+            rows * OVERVIEW_ROW_HEIGHT + (rows - 1.0) * OVERVIEW_ROW_SPACING + 12.0,
+        )
+    }
+
+    fn focus_monitor(&self, name: &str) {
+        hyprctl::run(["dispatch", "focusmonitor", name]);
+    }
+
+    /// Compositor-wide view: one row per monitor showing its active workspace
+    fn show_overview(&mut self, ui: &mut Ui) {
+        let mut monitor_to_focus = None;
+
+        ui.vertical(|ui| {
+            ui.spacing_mut().item_spacing = Vec2::new(0.0, OVERVIEW_ROW_SPACING);
+
+            for monitor in &self.monitors {
+                let is_current = monitor.active_workspace.id == self.current_workspace;
+                let text_color = if is_current {
+                    self.colors.primary_fixed_dim
+                } else {
+                    self.colors.on_surface_variant
+                };
+
+                let response = ui.add(
+                    Button::new("")
+                        .min_size(Vec2::new(OVERVIEW_ROW_WIDTH, OVERVIEW_ROW_HEIGHT))
+                        .fill(if is_current { self.colors.surface_container_high } else { Color32::from_black_alpha(128) })
+                        // Overview rows never get rounder than the buttons
+                        .corner_radius(CornerRadius::same(super::corner_radius(self.options.rounding, 10).min(10)))
+                        .stroke((if is_current { 2.0 } else { 0.0 }, self.colors.primary_fixed_dim))
+                );
+
+                ui.painter().text(
+                    response.rect.left_center() + Vec2::new(12.0, 0.0),
+                    Align2::LEFT_CENTER,
+                    &monitor.name,
+                    FontId::new(14.0, FontFamily::Proportional),
+                    text_color,
+                );
+                ui.painter().text(
+                    response.rect.right_center() - Vec2::new(12.0, 0.0),
+                    Align2::RIGHT_CENTER,
+                    &monitor.active_workspace.name,
+                    FontId::new(14.0, FontFamily::Proportional),
+                    text_color,
+                );
+
+                if response.clicked() {
+                    monitor_to_focus = Some(monitor.name.clone());
+                }
+            }
+        });
+
+        if let Some(name) = monitor_to_focus {
+            self.focus_monitor(&name);
+            self.update();
+        }
+        // Escape is handled by the app, like for every widget
+        let table = self.key_table();
+        if !ui.input(|i| shortcut::pressed(i, &table)).is_empty() {
+            self.close_requested = true;
+        }
     }
 
-    pub fn workspace_count(&self) -> usize {
-        self.workspaces.len()
+    /// Number of regular workspaces, which get full-size buttons
+    fn workspace_count(&self) -> usize {
+        self.workspaces.iter().filter(|w| !w.is_special() && self.is_shown(w)).count()
     }
 
-    fn get_app_icon(&self, ui: &mut Ui, class_name: &str) -> Option<TextureHandle> {
-        self.icon_cache.get_or_load(ui, class_name)
+    /// False for workspaces without windows when --hide-empty is set, except the
+    /// current and highlighted ones
+    fn is_shown(&self, workspace: &Workspace) -> bool {
+        !self.options.hide_empty
+            || workspace.id == self.current_workspace
+            || self.selected_workspace == Some(workspace.id)
+            || self.windows.iter().any(|w| w.workspace.id == workspace.id && w.class != "hypowertools")
     }
 
-    pub fn show(&mut self, ui: &mut Ui) {
+    /// Extra width taken by the special workspace buttons after the regular ones
+    fn special_group_width(&self) -> f32 {
+        let count = self.workspaces.iter().filter(|w| w.is_special() && self.is_shown(w)).count();
+        if count == 0 {
+            return 0.0;
+        }
+        SPECIAL_GROUP_GAP + count as f32 * (SPECIAL_BUTTON_WIDTH + 10.0)
+    }
+
+    fn get_app_icon(&self, ui: &mut Ui, class_name: &str, pid: i32) -> Option<TextureHandle> {
+        self.icon_cache.get_or_load(ui, class_name, pid)
+    }
+
+    /// Outlined button for a scratchpad, with its name and how many windows it holds
+    fn show_special_workspace(&self, ui: &mut Ui, workspace: &Workspace, window_count: usize) -> eframe::egui::Response {
+        let colors = &self.colors;
+        let radius = super::corner_radius(self.options.rounding, BUTTON_RADIUS);
+        let response = ui.add(
+            Button::new("")
+                .min_size(Vec2::new(SPECIAL_BUTTON_WIDTH, 80.0))
+                .fill(colors.surface_container.gamma_multiply(0.6))
+                .corner_radius(CornerRadius::same(radius))
+                .stroke((1.5, colors.outline))
+        );
+
+        let rect = response.rect;
+        ui.painter().text(
+            rect.center() - Vec2::new(0.0, 10.0),
+            Align2::CENTER_CENTER,
+            egui_phosphor::regular::SPARKLE,
+            FontId::new(22.0, FontFamily::Proportional),
+            colors.primary_fixed_dim,
+        );
+        ui.painter().text(
+            rect.center_bottom() - Vec2::new(0.0, self.options.label_margin),
+            Align2::CENTER_BOTTOM,
+            workspace.special_name(),
+            FontId::new(12.0, FontFamily::Proportional),
+            colors.on_surface_variant,
+        );
+        if window_count > 0 {
+            ui.painter().text(
+                rect.right_top() + Vec2::new(-8.0, 6.0),
+                Align2::RIGHT_TOP,
+                window_count.to_string(),
+                FontId::new(11.0, FontFamily::Proportional),
+                colors.on_surface_variant,
+            );
+        }
+        response.on_hover_text(format!("Toggle {}", workspace.name))
+    }
+
+    /// Draws the buttons, or the overview rows
+    fn draw(&mut self, ui: &mut Ui) {
+        if self.options.overview {
+            self.show_overview(ui);
+            return;
+        }
+
         // Load background image if not loaded
-        if self.background.is_none() {
-            if let Some(path) = Self::get_background_path() {
+        if self.options.background && self.background.is_none() {
+            if let Some(path) = self.get_background_path() {
                 let _ = image::io::Reader::open(&path)
                     .map_err(|_| ())
                     .and_then(|reader| reader.decode().map_err(|_| ()))
                     .map(|image| {
+                        // Buttons are tiny, so don't upload a full-resolution wallpaper
+                        let image = if image.width().max(image.height()) > BACKGROUND_MAX_SIZE {
+                            image.thumbnail(BACKGROUND_MAX_SIZE, BACKGROUND_MAX_SIZE)
+                        } else {
+                            image
+                        };
                         let size = [image.width() as _, image.height() as _];
                         let pixels = image.to_rgba8();
                         self.background = Some(ui.ctx().load_texture(
@@ -482,147 +1022,216 @@ impl WorkspaceSwitcher {
         }
 
         let mut workspace_to_switch = None;
+        let mut workspace_to_launch = None;
+        let mut special_to_toggle = None;
+        // Icon being dragged (None inside when it has no texture), and where a drag ended
+        let mut dragged_icon: Option<Option<TextureHandle>> = None;
+        let mut dropped_windows = None;
+        // Workspace id, dispatch target and button rect of everything an icon can be dropped on
+        let mut drop_targets: Vec<(i32, String, Rect)> = Vec::new();
         let mut should_close = false;
-        let windows = Self::get_windows();
-        let workspaces = self.workspaces.clone();
+        // Navigation keys are typed into the name while renaming, or into a
+        // text field elsewhere in the window such as a Wi-Fi password
+        let keys_enabled = self.renaming.is_none() && !ui.ctx().wants_keyboard_input();
+        let windows = &self.windows;
+        // Special workspaces get their own group and are left out of key navigation
+        let (special, workspaces): (Vec<Workspace>, Vec<Workspace>) = self.workspaces.iter()
+            .filter(|w| self.is_shown(w))
+            .cloned()
+            .partition(Workspace::is_special);
         let current_workspace = self.current_workspace;
         let colors = &self.colors;
 
-        // Handle arrow key navigation and Tab
-        if ui.input(|i| i.key_pressed(Key::ArrowLeft)) {
-            if let Some(current_idx) = workspaces.iter().position(|w| w.id == current_workspace) {
-                if current_idx > 0 {
-                    workspace_to_switch = Some(workspaces[current_idx - 1].id);
+        // Escape is handled by the app, like for every widget
+        let mut overview_requested = false;
+        let table = self.key_table();
+        let pressed = if keys_enabled { ui.input(|i| shortcut::pressed(i, &table)) } else { Vec::new() };
+        // The arrow keys and scrolling step through the workspaces, looked up before the loop takes them
+        let current_idx = workspaces.iter().position(|w| w.id == current_workspace);
+        let previous_id = current_idx.and_then(|idx| idx.checked_sub(1)).and_then(|idx| workspaces.get(idx)).map(|w| w.id);
+        let next_id = current_idx.and_then(|idx| workspaces.get(idx + 1)).map(|w| w.id);
+        for action in pressed {
+            match action {
+                SwitcherKey::Previous => workspace_to_switch = previous_id.or(workspace_to_switch),
+                SwitcherKey::Next => workspace_to_switch = next_id.or(workspace_to_switch),
+                SwitcherKey::Workspace(number) => {
+                    // Find workspace with this number, or at this position when remapped
+                    let target = if self.options.remap_indices {
+                        workspaces.get(number as usize - 1)
+                    } else {
+                        workspaces.iter().find(|w| w.id == number)
+                    };
+                    if let Some(workspace) = target {
+                        workspace_to_switch = Some(workspace.id);
+                        should_close = self.options.close_policy == ClosePolicy::NumberKeys;
+                    }
                 }
-            }
-        }
-        if ui.input(|i| i.key_pressed(Key::ArrowRight) || i.key_pressed(Key::Tab)) {
-            if let Some(current_idx) = workspaces.iter().position(|w| w.id == current_workspace) {
-                if current_idx < workspaces.len() - 1 {
-                    workspace_to_switch = Some(workspaces[current_idx + 1].id);
+                // Handed off to the full overview after the UI
+                SwitcherKey::Overview => overview_requested = true,
+                // Renames the highlighted workspace, or the current one
+                SwitcherKey::Rename => {
+                    let target = self.selected_workspace.unwrap_or(current_workspace);
+                    if let Some(workspace) = workspaces.iter().find(|w| w.id == target) {
+                        self.renaming = Some((workspace.id, workspace.name.clone()));
+                    }
                 }
-            }
-        }
-
-        // Handle number keys for direct workspace switching
-        for key in [
-            Key::Num0, Key::Num1, Key::Num2, Key::Num3, Key::Num4,
-            Key::Num5, Key::Num6, Key::Num7, Key::Num8, Key::Num9,
-        ] {
-            if ui.input(|i| i.key_pressed(key)) {
-                let num = match key {
-                    Key::Num0 => 10,
-                    Key::Num1 => 1,
-                    Key::Num2 => 2,
-                    Key::Num3 => 3,
-                    Key::Num4 => 4,
-                    Key::Num5 => 5,
-                    Key::Num6 => 6,
-                    Key::Num7 => 7,
-                    Key::Num8 => 8,
-                    Key::Num9 => 9,
-                    _ => continue,
-                };
-                
-                // Find workspace with this number
-                if let Some(workspace) = workspaces.iter().find(|w| w.id == num) {
-                    workspace_to_switch = Some(workspace.id);
+                // Enter confirms a pre-highlighted workspace
+                SwitcherKey::Confirm => {
+                    if let Some(selected) = self.selected_workspace {
+                        workspace_to_switch = Some(selected);
+                    }
                     should_close = true;
                 }
+                SwitcherKey::Close => should_close = true,
             }
         }
 
-        // Handle closing conditions
-        if ui.input(|i| i.key_pressed(Key::Escape) || i.key_pressed(Key::Enter)) {
-            should_close = true;
+        // Enter commits a non-empty name, Escape abandons it
+        let mut rename_to = None;
+        if let Some((workspace_id, name)) = &self.renaming {
+            if ui.input(|i| i.key_pressed(Key::Escape)) {
+                self.renaming = None;
+            } else if ui.input(|i| i.key_pressed(Key::Enter)) && !name.trim().is_empty() {
+                rename_to = Some((*workspace_id, name.trim().to_string()));
+                self.renaming = None;
+            }
         }
+        let mut rename_start = None;
+        // Taken out for the loop below, which also borrows `self` for the icons
+        let mut renaming = self.renaming.take();
 
-        ui.horizontal(|ui| {
-            for workspace in workspaces {
+
+        let strip = ui.horizontal(|ui| {
+            for (index, workspace) in workspaces.into_iter().enumerate() {
                 let is_current = workspace.id == current_workspace;
+                let is_selected = self.selected_workspace == Some(workspace.id);
+                // Remapped buttons show their position but still dispatch the real id
+                let label = if self.options.remap_indices {
+                    (index + 1).to_string()
+                } else {
+                    workspace.name.clone()
+                };
                 
                 let height = 80.0;
                 let width = (height * 16.0) / 9.0;
-                let rounding = Rounding::same(15);
+                let button_radius = super::corner_radius(self.options.rounding, BUTTON_RADIUS);
+                let rounding = CornerRadius::same(button_radius);
                 
                 let button = Button::new("")
                     .min_size(Vec2::new(width, height))
-                    .fill(if is_current { colors.surface_container_high } else { Color32::from_black_alpha(128) })
-                    .rounding(rounding)
-                    .stroke((
-                        if is_current { 2.0 } else { 0.0 },
-                        colors.primary_fixed_dim
-                    ))
+                    .fill(if is_current {
+                        colors.surface_container_high
+                    } else if self.options.background {
+                        Color32::from_black_alpha(128)
+                    } else {
+                        colors.surface_container
+                    })
+                    .corner_radius(rounding)
+                    .stroke(if is_current {
+                        (2.0, colors.primary_fixed_dim)
+                    } else if is_selected {
+                        (2.0, colors.on_surface_variant)
+                    } else {
+                        (0.0, colors.primary_fixed_dim)
+                    })
                     .frame(false);
                 
                 let response = ui.add(button);
+                drop_targets.push((workspace.id, workspace.id.to_string(), response.rect));
 
                 // Draw background image if available
                 if let Some(bg) = &self.background {
-                    // Create a slightly smaller rect for the background
-                    let inner_rect = response.rect.shrink(2.0);
+                    // Create a slightly smaller rect for the background, with corners
+                    // concentric to the button's so none poke out at any radius
+                    let inner_rect = response.rect.shrink(BACKGROUND_INSET as f32);
+                    let inner_rounding = CornerRadius::same(button_radius.saturating_sub(BACKGROUND_INSET));
                     
                     // First draw the background image
                     Image::new(bg)
-                        .rounding(Rounding::same(15))
+                        .corner_radius(inner_rounding)
                         .fit_to_exact_size(inner_rect.size())
                         .paint_at(ui, inner_rect);
 
                     // Add multiple layers for a better blur/dim effect
                     ui.painter().rect_filled(
                         inner_rect,
-                        Rounding::same(15),
-                        Color32::from_black_alpha(120), // First layer of dimming
+                        inner_rounding,
+                        Color32::from_black_alpha((self.options.bg_dim * 255.0).round() as u8), // First layer of dimming
                     );
                     
                     // Add a subtle colored overlay
                     ui.painter().rect_filled(
                         inner_rect,
-                        Rounding::same(15),
-                        colors.surface.gamma_multiply(0.3), // Second layer with surface color
+                        inner_rounding,
+                        colors.surface.gamma_multiply(self.options.bg_tint), // Second layer with surface color
                     );
                     
                     // Add extra overlay for current workspace
                     if is_current {
                         ui.painter().rect_filled(
                             inner_rect,
-                            Rounding::same(15),
+                            inner_rounding,
                             Color32::from_black_alpha(80),
                         );
                     }
                 }
 
-                // Draw workspace number (bottom left)
-                let workspace_pos = response.rect.left_bottom() + Vec2::new(8.0, -8.0);
-                ui.painter().text(
-                    workspace_pos,
-                    Align2::LEFT_BOTTOM,
-                    &workspace.name,
-                    FontId::new(14.0, FontFamily::Proportional),
-                    if is_current {
-                        colors.primary_fixed_dim
-                    } else {
-                        colors.on_surface_variant
-                    },
-                );
+                // Draw workspace number (bottom left), or the name field while renaming
+                let label_margin = self.options.label_margin;
+                let workspace_pos = response.rect.left_bottom() + Vec2::new(label_margin, -label_margin);
+                match renaming.as_mut().filter(|(id, _)| *id == workspace.id) {
+                    Some((_, name)) => {
+                        let edit_rect = Rect::from_min_max(
+                            workspace_pos - Vec2::new(0.0, 22.0),
+                            response.rect.right_bottom() - Vec2::splat(label_margin),
+                        );
+                        let edit = ui.put(
+                            edit_rect,
+                            TextEdit::singleline(name)
+                                .font(FontId::new(14.0, FontFamily::Proportional))
+                                .text_color(colors.primary_fixed_dim),
+                        );
+                        edit.request_focus();
+                    }
+                    None => {
+                        ui.painter().text(
+                            workspace_pos,
+                            Align2::LEFT_BOTTOM,
+                            &label,
+                            FontId::new(14.0, FontFamily::Proportional),
+                            if is_current {
+                                colors.primary_fixed_dim
+                            } else {
+                                colors.on_surface_variant
+                            },
+                        );
+                    }
+                }
 
                 // Draw app icons (top left)
-                let workspace_windows: Vec<String> = windows.iter()
+                let workspace_windows: Vec<&Window> = windows.iter()
                     .filter(|w| w.workspace.id == workspace.id && w.class != "hypowertools")
-                    .map(|w| w.class.clone())
-                    .collect::<Vec<String>>();
+                    .collect();
 
-                let unique_windows: Vec<&String> = workspace_windows.iter()
+                let unique_windows: Vec<&Window> = workspace_windows.iter()
                     .enumerate()
-                    .filter(|(i, app)| workspace_windows[..*i].iter().find(|&x| x == *app).is_none())
-                    .map(|(_, app)| app)
+                    .filter(|(i, window)| !workspace_windows[..*i].iter().any(|w| w.class == window.class))
+                    .map(|(_, window)| *window)
                     .collect();
 
                 if !workspace_windows.is_empty() {
                     let icon_size = 26.0; // Reduced from 32.0 to 26.0
                     let icon_spacing = 4.0; // Reduced spacing
-                    let icon_margin = 8.0;
-                    let icon_area_width = (icon_size + icon_spacing) * 3.0 - icon_spacing;
+                    let icon_margin = self.options.icon_margin;
+                    // Never draw past the button: --max-icons is capped by how many fit,
+                    // leaving room for the +N label whenever some are left out
+                    let icons_fitting = |room: f32| ((room + icon_spacing) / (icon_size + icon_spacing)).max(0.0) as usize;
+                    let room = response.rect.width() - 2.0 * icon_margin;
+                    let mut max_icons = self.options.max_icons.min(icons_fitting(room));
+                    if unique_windows.len() > max_icons {
+                        max_icons = max_icons.min(icons_fitting(room - OVERFLOW_LABEL_WIDTH));
+                    }
+                    let icon_area_width = ((icon_size + icon_spacing) * max_icons as f32 - icon_spacing).max(0.0);
                     
                     // Create a container for icons at the top of the workspace button
                     let icon_area = Rect::from_min_size(
@@ -633,65 +1242,320 @@ impl WorkspaceSwitcher {
                         Vec2::new(icon_area_width, icon_size)
                     );
 
-                    for (idx, app_class) in unique_windows.iter().take(3).enumerate() {
-                        // Special handling for Cursor
-                        let lookup_name = if **app_class == "Cursor" {
-                            "cursor"  // Try lowercase
-                        } else {
-                            app_class
-                        };
-                        
-                        if let Some(icon) = self.get_app_icon(ui, lookup_name) {
-                            let icon_rect = Rect::from_min_size(
-                                Pos2::new(
-                                    icon_area.left() + (icon_size + icon_spacing) * idx as f32,
-                                    icon_area.top()
-                                ),
-                                Vec2::new(icon_size, icon_size)
-                            );
-                            
-                            Image::new(&icon)
+                    for (idx, window) in unique_windows.iter().take(max_icons).enumerate() {
+                        let icon_rect = Rect::from_min_size(
+                            Pos2::new(
+                                icon_area.left() + (icon_size + icon_spacing) * idx as f32,
+                                icon_area.top()
+                            ),
+                            Vec2::new(icon_size, icon_size)
+                        );
+
+                        // Icons can be dragged onto another workspace to move their windows there
+                        let drag_id = Id::new(("window-icon", &window.address));
+                        let is_dragged = !window.address.is_empty() && ui.ctx().dragged_id() == Some(drag_id);
+                        let tint = if is_dragged { Color32::WHITE.gamma_multiply(0.35) } else { Color32::WHITE };
+
+                        let icon = self.get_app_icon(ui, &window.class, window.pid);
+                        if let Some(icon) = &icon {
+                            Image::new(icon)
+                                .tint(tint)
                                 .fit_to_exact_size(Vec2::new(icon_size, icon_size))
                                 .paint_at(ui, icon_rect);
+                        } else {
+                            // Generic placeholder so unresolved icons don't leave a gap
+                            ui.painter().text(
+                                icon_rect.center(),
+                                Align2::CENTER_CENTER,
+                                egui_phosphor::regular::APP_WINDOW,
+                                FontId::new(icon_size * 0.8, FontFamily::Proportional),
+                                colors.on_surface_variant.gamma_multiply(if is_dragged { 0.35 } else { 1.0 }),
+                            );
+                        }
+
+                        if !window.address.is_empty() {
+                            let drag = ui.interact(icon_rect, drag_id, Sense::drag()).on_hover_cursor(CursorIcon::Grab);
+                            if drag.dragged() {
+                                dragged_icon = Some(icon.clone());
+                            }
+                            if drag.drag_stopped() {
+                                // One icon stands for every window of its app on this workspace
+                                let addresses = workspace_windows.iter()
+                                    .filter(|w| w.class == window.class && !w.address.is_empty())
+                                    .map(|w| w.address.clone())
+                                    .collect::<Vec<_>>();
+                                dropped_windows = Some((addresses, workspace.id));
+                            }
+                        }
+
+                        // Badge apps still running through XWayland
+                        let is_xwayland = workspace_windows.iter().any(|w| w.class == window.class && w.xwayland);
+                        if self.options.mark_xwayland && is_xwayland {
+                            let badge = Rect::from_min_size(
+                                icon_rect.right_bottom() - Vec2::new(16.0, 9.0),
+                                Vec2::new(18.0, 10.0),
+                            );
+                            ui.painter().rect_filled(badge, 3.0, colors.on_primary_fixed);
+                            ui.painter().text(
+                                badge.center(),
+                                Align2::CENTER_CENTER,
+                                "X11",
+                                FontId::new(7.0, FontFamily::Proportional),
+                                colors.primary_fixed_dim,
+                            );
                         }
                     }
 
-                    if unique_windows.len() > 3 {
+                    if unique_windows.len() > max_icons {
                         let text_pos = Pos2::new(
-                            icon_area.right() + 6.0,
+                            icon_area.left() + icon_area_width + if max_icons > 0 { 6.0 } else { 0.0 },
                             icon_area.center().y
                         );
                         ui.painter().text(
                             text_pos,
                             Align2::LEFT_CENTER,
-                            &format!("+{}", unique_windows.len() - 3),
+                            format!("+{}", unique_windows.len() - max_icons),
                             FontId::new(11.0, FontFamily::Proportional),
                             if is_current { colors.primary_fixed_dim } else { colors.on_surface_variant },
                         );
                     }
                 }
                 
+                // Pulse a ring outward from a freshly switched-to workspace
+                if let Some((pulse_id, started)) = self.switch_pulse {
+                    let t = ((ui.input(|i| i.time) - started) / SWITCH_PULSE_SECS) as f32;
+                    if pulse_id == workspace.id && t < 1.0 {
+                        ui.painter().rect_stroke(
+                            response.rect.expand(4.0 * t),
+                            rounding,
+                            Stroke::new(3.0 * (1.0 - t), colors.primary_fixed_dim.gamma_multiply(1.0 - t)),
+                            StrokeKind::Outside,
+                        );
+                        super::request_repaint(ui.ctx(), self.options.frame_interval);
+                    }
+                }
+
                 if response.clicked() {
                     workspace_to_switch = Some(workspace.id);
                 }
+                if response.middle_clicked() {
+                    workspace_to_launch = Some(workspace.id);
+                }
+                response.context_menu(|ui| {
+                    if ui.button("Rename").clicked() {
+                        rename_start = Some((workspace.id, workspace.name.clone()));
+                        ui.close_menu();
+                    }
+                });
+            }
+
+            if !special.is_empty() {
+                ui.add_space(SPECIAL_GROUP_GAP);
             }
+            for workspace in &special {
+                let window_count = windows.iter().filter(|w| w.workspace.id == workspace.id).count();
+                let response = self.show_special_workspace(ui, workspace, window_count);
+                drop_targets.push((workspace.id, workspace.name.clone(), response.rect));
+                if response.clicked() {
+                    special_to_toggle = Some(workspace.special_name().to_string());
+                }
+            }
+        });
+
+        // While an icon is dragged it follows the pointer and the workspace under it is outlined
+        let pointer = ui.input(|i| i.pointer.interact_pos());
+        let target_at = |pos: Pos2| drop_targets.iter().find(|(_, _, rect)| rect.contains(pos));
+        if let (Some(icon), Some(pos)) = (&dragged_icon, pointer) {
+            ui.ctx().set_cursor_icon(CursorIcon::Grabbing);
+            if let Some((_, _, rect)) = target_at(pos) {
+                let radius = super::corner_radius(self.options.rounding, BUTTON_RADIUS);
+                ui.painter().rect_stroke(*rect, radius, Stroke::new(2.0, colors.primary_fixed_dim), StrokeKind::Outside);
+            }
+
+            let painter = ui.ctx().layer_painter(LayerId::new(Order::Tooltip, Id::new("dragged-window")));
+            let ghost = Rect::from_center_size(pos, Vec2::splat(DRAG_ICON_SIZE));
+            match icon {
+                Some(icon) => {
+                    let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+                    painter.image(icon.id(), ghost, uv, Color32::WHITE);
+                }
+                None => {
+                    painter.text(
+                        ghost.center(),
+                        Align2::CENTER_CENTER,
+                        egui_phosphor::regular::APP_WINDOW,
+                        FontId::new(DRAG_ICON_SIZE * 0.8, FontFamily::Proportional),
+                        colors.on_surface_variant,
+                    );
+                }
+            }
+        }
+        let windows_to_move = dropped_windows.zip(pointer).and_then(|((addresses, from), pos)| {
+            target_at(pos)
+                .filter(|(id, _, _)| *id != from)
+                .map(|(_, target, _)| (addresses, target.clone()))
         });
 
+        if keys_enabled && ui.rect_contains_pointer(strip.response.rect) {
+            let scroll = ui.input(|i| i.raw_scroll_delta.y);
+            let settled = self.last_scroll_switch.is_none_or(|at| at.elapsed() > SCROLL_DEBOUNCE);
+            let target = if scroll > 0.0 { previous_id } else if scroll < 0.0 { next_id } else { None };
+            if let Some(target) = target.filter(|_| settled) {
+                workspace_to_switch = Some(target);
+                self.last_scroll_switch = Some(Instant::now());
+            }
+        }
+
         // Handle actions after UI
+        self.renaming = rename_start.or(renaming);
+        if let Some((workspace_id, name)) = rename_to {
+            self.rename_workspace(workspace_id, &name);
+        }
+        if overview_requested && self.open_overview() {
+            should_close = true;
+        }
+        if let Some((addresses, target)) = windows_to_move {
+            self.move_windows(&addresses, &target);
+        }
+        if let Some(name) = special_to_toggle {
+            if self.options.close_policy == ClosePolicy::Once {
+                should_close = true;
+            }
+            self.toggle_special_workspace(&name);
+        }
+        if let Some(workspace_id) = workspace_to_launch {
+            self.launch_on_workspace(workspace_id);
+        }
         if let Some(workspace_id) = workspace_to_switch {
+            if self.options.close_policy == ClosePolicy::Once {
+                should_close = true;
+            }
+            self.selected_workspace = None;
             self.switch_to_workspace(workspace_id);
             self.update();
+            if !self.options.reduce_motion {
+                self.switch_pulse = Some((workspace_id, ui.input(|i| i.time)));
+            }
         }
         if should_close {
-            ui.ctx().send_viewport_cmd(ViewportCommand::Close);
+            self.close_requested = true;
+        }
+    }
+}
+
+impl Panel for WorkspaceSwitcher {
+    fn shortcuts(&self) -> Vec<(String, String)> {
+        self.key_table().iter().map(Shortcut::help).collect()
+    }
+
+    fn close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    /// True while a workspace name is being typed, so keys go to the text field
+    fn is_editing(&self) -> bool {
+        self.renaming.is_some()
+    }
+
+    fn next_update(&self) -> Option<Duration> {
+        Some(self.options.refresh_interval.saturating_sub(self.last_update.elapsed()))
+    }
+
+    fn should_update(&self) -> bool {
+        self.last_update.elapsed() > self.options.refresh_interval
+    }
+
+    fn update(&mut self) {
+        self.workspaces = Self::get_workspaces();
+        self.windows = Self::get_windows();
+        self.current_workspace = Self::get_current_workspace();
+        if self.options.overview || self.options.monitor_workspaces {
+            // Keep the last known layout while the compositor is reloading
+            let monitors = get_monitors();
+            if !monitors.is_empty() {
+                self.monitors = monitors;
+            }
+        }
+        if self.options.monitor_workspaces {
+            self.filter_to_monitor();
+        }
+        self.last_update = Instant::now();
+    }
+
+    fn colors(&self) -> &super::Colors {
+        &self.colors
+    }
+
+    /// Measured once drawn; until then estimated from the workspaces
+    fn size(&self) -> Vec2 {
+        if let Some(size) = self.shown_size {
+            return size;
         }
+        if self.is_overview() {
+            return self.overview_size();
+        }
+
+        // Each workspace button is ~142px wide (80px height * 16/9 aspect ratio + spacing)
+        // Add padding (12px) and margin (10px spacing between items)
+        let count = self.workspace_count();
+        let button_width = 142.0;
+        let spacing = 10.0;
+        let padding = 12.0; // 6px on each side
+
+        // Calculate total width including padding and spacing
+        let width = (count as f32 * button_width) + // Width of all buttons
+                  ((count.saturating_sub(1)) as f32 * spacing) + // Spacing between buttons
+                  padding + // Total padding (6px on each side)
+                  self.special_group_width(); // Scratchpads after the regular workspaces
+
+        // Keep height fixed at 92px
+        Vec2::new(width, 92.0)
     }
 
-    pub fn cleanup(&mut self) {
-        // Drop all cached textures to ensure proper cleanup
-        self.icon_cache.cache.borrow_mut().clear();
-        // Drop background texture if it exists
-        self.background = None;
+    fn min_size(&self) -> Vec2 {
+        if self.is_overview() {
+            // One row per monitor; height follows the monitor count
+            Vec2::new(100.0, 40.0)
+        } else {
+            // One workspace: 142px (button) + 12px (padding)
+            Vec2::new(154.0, 92.0)
+        }
     }
 
-} 
\ No newline at end of file
+    fn max_size(&self) -> Vec2 {
+        if self.is_overview() {
+            Vec2::new(1024.0, 1024.0)
+        } else {
+            Vec2::new(1024.0, 92.0)
+        }
+    }
+
+    fn margin(&self) -> f32 {
+        6.0
+    }
+
+    fn corner_radius(&self) -> u8 {
+        15
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.is_overview() {
+            ui.set_min_height(80.0);
+        }
+        ui.spacing_mut().button_padding = Vec2::ZERO;
+        ui.spacing_mut().item_spacing = Vec2::new(10.0, 0.0);
+
+        self.draw(ui);
+
+        let rect = ui.min_rect();
+        self.shown_size = Some(if self.is_overview() {
+            Vec2::new(rect.width() + 12.0, rect.height() + 12.0)
+        } else {
+            Vec2::new(rect.width() + 12.0, 92.0)
+        });
+    }
+
+    fn resizable(&self) -> bool {
+        true
+    }
+}